@@ -0,0 +1,1105 @@
+use crate::theme::Theme;
+
+/// The kinds of values a functional utility like `bg-*` accepts.
+#[derive(Clone, Copy)]
+enum Kind {
+    Color,
+    Spacing,
+    /// Fractions like `1/2`
+    Fraction,
+    Font,
+    /// Font sizes, with an optional line height modifier like `sm/6`
+    FontSize,
+    Keywords(&'static [&'static str]),
+}
+
+use Kind::*;
+
+static COLORS: &[&str] = &[
+    "slate", "gray", "zinc", "neutral", "stone", "red", "orange", "amber", "yellow", "lime",
+    "green", "emerald", "teal", "cyan", "sky", "blue", "indigo", "violet", "purple", "fuchsia",
+    "pink", "rose",
+];
+static SHADES: &[&str] = &[
+    "50", "100", "200", "300", "400", "500", "600", "700", "800", "900", "950",
+];
+static SPECIAL_COLORS: &[&str] = &["inherit", "current", "transparent", "black", "white"];
+static SPACING: &[&str] = &[
+    "0", "px", "0.5", "1", "1.5", "2", "2.5", "3", "3.5", "4", "5", "6", "7", "8", "9", "10", "11",
+    "12", "14", "16", "20", "24", "28", "32", "36", "40", "44", "48", "52", "56", "60", "64", "72",
+    "80", "96",
+];
+static FRACTIONS: &[&str] = &[
+    "1/2", "1/3", "2/3", "1/4", "2/4", "3/4", "1/5", "2/5", "3/5", "4/5", "1/6", "2/6", "3/6",
+    "4/6", "5/6", "1/12", "2/12", "3/12", "4/12", "5/12", "6/12", "7/12", "8/12", "9/12", "10/12",
+    "11/12",
+];
+static FONTS: &[&str] = &["sans", "serif", "mono"];
+
+static INSET: &[&str] = &["auto", "full"];
+static SIZES: &[&str] = &[
+    "auto", "full", "screen", "svw", "lvw", "dvw", "svh", "lvh", "dvh", "min", "max", "fit",
+];
+static MAX_WIDTHS: &[&str] = &[
+    "none",
+    "xs",
+    "sm",
+    "md",
+    "lg",
+    "xl",
+    "2xl",
+    "3xl",
+    "4xl",
+    "5xl",
+    "6xl",
+    "7xl",
+    "full",
+    "min",
+    "max",
+    "fit",
+    "prose",
+    "screen-sm",
+    "screen-md",
+    "screen-lg",
+    "screen-xl",
+    "screen-2xl",
+];
+static FONT_SIZES: &[&str] = &[
+    "xs", "sm", "base", "lg", "xl", "2xl", "3xl", "4xl", "5xl", "6xl", "7xl", "8xl", "9xl",
+];
+static TEXT: &[&str] = &[
+    "left", "center", "right", "justify", "start", "end", "ellipsis", "clip", "wrap", "nowrap",
+    "balance", "pretty",
+];
+static FONT: &[&str] = &[
+    "thin",
+    "extralight",
+    "light",
+    "normal",
+    "medium",
+    "semibold",
+    "bold",
+    "extrabold",
+    "black",
+];
+static RADII: &[&str] = &[
+    "none", "xs", "sm", "md", "lg", "xl", "2xl", "3xl", "4xl", "full",
+];
+static WIDTHS: &[&str] = &["0", "2", "4", "8"];
+static BORDER: &[&str] = &[
+    "0", "2", "4", "8", "solid", "dashed", "dotted", "double", "hidden", "none", "collapse",
+    "separate", "spacing",
+];
+static SHADOWS: &[&str] = &["2xs", "xs", "sm", "md", "lg", "xl", "2xl", "inner", "none"];
+static PERCENTS: &[&str] = &[
+    "0", "5", "10", "15", "20", "25", "30", "35", "40", "45", "50", "55", "60", "65", "70", "75",
+    "80", "85", "90", "95", "100",
+];
+static Z: &[&str] = &["0", "10", "20", "30", "40", "50", "auto"];
+static LEADING: &[&str] = &[
+    "none", "tight", "snug", "normal", "relaxed", "loose", "3", "4", "5", "6", "7", "8", "9", "10",
+];
+static TRACKING: &[&str] = &["tighter", "tight", "normal", "wide", "wider", "widest"];
+static GRID: &[&str] = &[
+    "1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11", "12", "none", "subgrid",
+];
+static SPAN: &[&str] = &[
+    "1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11", "12", "full",
+];
+static LINES: &[&str] = &[
+    "1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11", "12", "13", "auto",
+];
+static ORDER: &[&str] = &[
+    "1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11", "12", "first", "last", "none",
+];
+static FLEX: &[&str] = &[
+    "1",
+    "auto",
+    "initial",
+    "none",
+    "row",
+    "row-reverse",
+    "col",
+    "col-reverse",
+    "wrap",
+    "wrap-reverse",
+    "nowrap",
+];
+static GROW: &[&str] = &["0"];
+static DURATIONS: &[&str] = &["0", "75", "100", "150", "200", "300", "500", "700", "1000"];
+static EASE: &[&str] = &["linear", "in", "out", "in-out"];
+static TRANSITION: &[&str] = &["none", "all", "colors", "opacity", "shadow", "transform"];
+static SCALE: &[&str] = &[
+    "0", "50", "75", "90", "95", "100", "105", "110", "125", "150",
+];
+static ROTATE: &[&str] = &["0", "1", "2", "3", "6", "12", "45", "90", "180"];
+static SKEW: &[&str] = &["0", "1", "2", "3", "6", "12"];
+static BLUR: &[&str] = &["none", "xs", "sm", "md", "lg", "xl", "2xl", "3xl"];
+static FILTERS: &[&str] = &[
+    "0", "50", "75", "90", "95", "100", "105", "110", "125", "150", "200",
+];
+static LINE_CLAMP: &[&str] = &["1", "2", "3", "4", "5", "6", "none"];
+static COLUMNS: &[&str] = &[
+    "1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11", "12", "auto", "3xs", "2xs", "xs",
+    "sm", "md", "lg", "xl", "2xl", "3xl", "4xl", "5xl", "6xl", "7xl",
+];
+static ASPECT: &[&str] = &["auto", "square", "video"];
+static POSITIONS: &[&str] = &[
+    "center",
+    "top",
+    "top-right",
+    "right",
+    "bottom-right",
+    "bottom",
+    "bottom-left",
+    "left",
+    "top-left",
+    "left-top",
+    "left-bottom",
+    "right-top",
+    "right-bottom",
+];
+static OBJECT: &[&str] = &[
+    "contain",
+    "cover",
+    "fill",
+    "none",
+    "scale-down",
+    "center",
+    "top",
+    "right",
+    "bottom",
+    "left",
+    "left-top",
+    "left-bottom",
+    "right-top",
+    "right-bottom",
+];
+static RING: &[&str] = &["0", "1", "2", "4", "8", "inset"];
+static OUTLINE: &[&str] = &[
+    "none", "hidden", "dashed", "dotted", "double", "0", "1", "2", "4", "8",
+];
+static OFFSETS: &[&str] = &["auto", "0", "1", "2", "4", "8"];
+static DIVIDE: &[&str] = &["0", "2", "4", "8", "reverse"];
+static DECORATION: &[&str] = &[
+    "solid",
+    "double",
+    "dotted",
+    "dashed",
+    "wavy",
+    "auto",
+    "from-font",
+    "0",
+    "1",
+    "2",
+    "4",
+    "8",
+    "slice",
+    "clone",
+];
+static CURSOR: &[&str] = &[
+    "auto",
+    "default",
+    "pointer",
+    "wait",
+    "text",
+    "move",
+    "help",
+    "not-allowed",
+    "none",
+    "context-menu",
+    "progress",
+    "cell",
+    "crosshair",
+    "vertical-text",
+    "alias",
+    "copy",
+    "no-drop",
+    "grab",
+    "grabbing",
+    "all-scroll",
+    "col-resize",
+    "row-resize",
+    "n-resize",
+    "e-resize",
+    "s-resize",
+    "w-resize",
+    "ne-resize",
+    "nw-resize",
+    "se-resize",
+    "sw-resize",
+    "ew-resize",
+    "ns-resize",
+    "nesw-resize",
+    "nwse-resize",
+    "zoom-in",
+    "zoom-out",
+];
+static OVERFLOW: &[&str] = &["auto", "hidden", "clip", "visible", "scroll"];
+static ALIGN: &[&str] = &[
+    "normal", "start", "end", "center", "between", "around", "evenly", "stretch", "baseline",
+    "auto",
+];
+static WHITESPACE: &[&str] = &[
+    "normal",
+    "nowrap",
+    "pre",
+    "pre-line",
+    "pre-wrap",
+    "break-spaces",
+];
+static BREAK: &[&str] = &[
+    "normal", "words", "all", "keep", "auto", "avoid", "after", "before", "inside",
+];
+static LIST: &[&str] = &["none", "disc", "decimal", "inside", "outside", "image-none"];
+static SELECT: &[&str] = &["none", "text", "all", "auto"];
+static POINTER_EVENTS: &[&str] = &["none", "auto"];
+static RESIZE: &[&str] = &["none", "x", "y"];
+static BG: &[&str] = &[
+    "fixed",
+    "local",
+    "scroll",
+    "auto",
+    "cover",
+    "contain",
+    "center",
+    "top",
+    "right",
+    "bottom",
+    "left",
+    "left-top",
+    "left-bottom",
+    "right-top",
+    "right-bottom",
+    "repeat",
+    "no-repeat",
+    "repeat-x",
+    "repeat-y",
+    "repeat-round",
+    "repeat-space",
+    "none",
+    "gradient-to-t",
+    "gradient-to-tr",
+    "gradient-to-r",
+    "gradient-to-br",
+    "gradient-to-b",
+    "gradient-to-bl",
+    "gradient-to-l",
+    "gradient-to-tl",
+    "linear-to-t",
+    "linear-to-tr",
+    "linear-to-r",
+    "linear-to-br",
+    "linear-to-b",
+    "linear-to-bl",
+    "linear-to-l",
+    "linear-to-tl",
+    "clip-border",
+    "clip-padding",
+    "clip-content",
+    "clip-text",
+    "origin-border",
+    "origin-padding",
+    "origin-content",
+    "blend-normal",
+    "blend-multiply",
+    "blend-screen",
+    "blend-overlay",
+];
+static STOPS: &[&str] = &[
+    "0%", "5%", "10%", "15%", "20%", "25%", "30%", "35%", "40%", "45%", "50%", "55%", "60%", "65%",
+    "70%", "75%", "80%", "85%", "90%", "95%", "100%",
+];
+static FILL: &[&str] = &["none"];
+static STROKE: &[&str] = &["none", "0", "1", "2"];
+static FLOAT: &[&str] = &["left", "right", "start", "end", "none"];
+static CLEAR: &[&str] = &["left", "right", "start", "end", "both", "none"];
+static GRID_FLOW: &[&str] = &["row", "col", "dense", "row-dense", "col-dense"];
+static AUTO_TRACKS: &[&str] = &["auto", "min", "max", "fr"];
+static BOX: &[&str] = &["border", "content", "decoration-slice", "decoration-clone"];
+static MIX_BLEND: &[&str] = &[
+    "normal",
+    "multiply",
+    "screen",
+    "overlay",
+    "darken",
+    "lighten",
+    "color-dodge",
+    "color-burn",
+    "hard-light",
+    "soft-light",
+    "difference",
+    "exclusion",
+    "hue",
+    "saturation",
+    "color",
+    "luminosity",
+    "plus-darker",
+    "plus-lighter",
+];
+static WILL_CHANGE: &[&str] = &["auto", "scroll", "contents", "transform"];
+static SNAP: &[&str] = &[
+    "start",
+    "end",
+    "center",
+    "align-none",
+    "normal",
+    "always",
+    "none",
+    "x",
+    "y",
+    "both",
+    "mandatory",
+    "proximity",
+];
+static TOUCH: &[&str] = &[
+    "auto",
+    "none",
+    "pan-x",
+    "pan-left",
+    "pan-right",
+    "pan-y",
+    "pan-up",
+    "pan-down",
+    "pinch-zoom",
+    "manipulation",
+];
+static ANIMATE: &[&str] = &["none", "spin", "ping", "pulse", "bounce"];
+static CONTENT: &[&str] = &["none"];
+static TABLE: &[&str] = &[
+    "auto",
+    "fixed",
+    "caption",
+    "cell",
+    "column",
+    "column-group",
+    "footer-group",
+    "header-group",
+    "row-group",
+    "row",
+];
+static HYPHENS: &[&str] = &["none", "manual", "auto"];
+static VERTICAL_ALIGN: &[&str] = &[
+    "baseline",
+    "top",
+    "middle",
+    "bottom",
+    "text-top",
+    "text-bottom",
+    "sub",
+    "super",
+];
+static APPEARANCE: &[&str] = &["none", "auto"];
+static SCROLL: &[&str] = &["auto", "smooth"];
+static FORCED_COLOR: &[&str] = &["auto", "none"];
+
+/// Functional utilities: their root, the values they accept and whether they can be negative.
+static FUNCTIONAL: &[(&str, &[Kind], bool)] = &[
+    ("p", &[Spacing], false),
+    ("px", &[Spacing], false),
+    ("py", &[Spacing], false),
+    ("pt", &[Spacing], false),
+    ("pr", &[Spacing], false),
+    ("pb", &[Spacing], false),
+    ("pl", &[Spacing], false),
+    ("ps", &[Spacing], false),
+    ("pe", &[Spacing], false),
+    ("m", &[Spacing, Keywords(INSET)], true),
+    ("mx", &[Spacing, Keywords(INSET)], true),
+    ("my", &[Spacing, Keywords(INSET)], true),
+    ("mt", &[Spacing, Keywords(INSET)], true),
+    ("mr", &[Spacing, Keywords(INSET)], true),
+    ("mb", &[Spacing, Keywords(INSET)], true),
+    ("ml", &[Spacing, Keywords(INSET)], true),
+    ("ms", &[Spacing, Keywords(INSET)], true),
+    ("me", &[Spacing, Keywords(INSET)], true),
+    ("gap", &[Spacing], false),
+    ("gap-x", &[Spacing], false),
+    ("gap-y", &[Spacing], false),
+    ("space-x", &[Spacing, Keywords(&["reverse"])], true),
+    ("space-y", &[Spacing, Keywords(&["reverse"])], true),
+    ("inset", &[Spacing, Fraction, Keywords(INSET)], true),
+    ("inset-x", &[Spacing, Fraction, Keywords(INSET)], true),
+    ("inset-y", &[Spacing, Fraction, Keywords(INSET)], true),
+    ("top", &[Spacing, Fraction, Keywords(INSET)], true),
+    ("right", &[Spacing, Fraction, Keywords(INSET)], true),
+    ("bottom", &[Spacing, Fraction, Keywords(INSET)], true),
+    ("left", &[Spacing, Fraction, Keywords(INSET)], true),
+    ("start", &[Spacing, Fraction, Keywords(INSET)], true),
+    ("end", &[Spacing, Fraction, Keywords(INSET)], true),
+    ("translate-x", &[Spacing, Fraction, Keywords(INSET)], true),
+    ("translate-y", &[Spacing, Fraction, Keywords(INSET)], true),
+    ("scroll-m", &[Spacing], true),
+    ("scroll-mx", &[Spacing], true),
+    ("scroll-my", &[Spacing], true),
+    ("scroll-mt", &[Spacing], true),
+    ("scroll-mr", &[Spacing], true),
+    ("scroll-mb", &[Spacing], true),
+    ("scroll-ml", &[Spacing], true),
+    ("scroll-ms", &[Spacing], true),
+    ("scroll-me", &[Spacing], true),
+    ("scroll-p", &[Spacing], false),
+    ("scroll-px", &[Spacing], false),
+    ("scroll-py", &[Spacing], false),
+    ("scroll-pt", &[Spacing], false),
+    ("scroll-pr", &[Spacing], false),
+    ("scroll-pb", &[Spacing], false),
+    ("scroll-pl", &[Spacing], false),
+    ("scroll-ps", &[Spacing], false),
+    ("scroll-pe", &[Spacing], false),
+    ("w", &[Spacing, Fraction, Keywords(SIZES)], false),
+    ("h", &[Spacing, Fraction, Keywords(SIZES)], false),
+    ("size", &[Spacing, Fraction, Keywords(SIZES)], false),
+    ("min-w", &[Spacing, Fraction, Keywords(SIZES)], false),
+    ("min-h", &[Spacing, Fraction, Keywords(SIZES)], false),
+    ("max-w", &[Spacing, Fraction, Keywords(MAX_WIDTHS)], false),
+    ("max-h", &[Spacing, Fraction, Keywords(SIZES)], false),
+    ("basis", &[Spacing, Fraction, Keywords(SIZES)], false),
+    ("bg", &[Color, Keywords(BG)], false),
+    ("text", &[Color, FontSize, Keywords(TEXT)], false),
+    ("font", &[Font, Keywords(FONT)], false),
+    ("border", &[Color, Keywords(BORDER)], false),
+    ("border-x", &[Color, Keywords(WIDTHS)], false),
+    ("border-y", &[Color, Keywords(WIDTHS)], false),
+    ("border-t", &[Color, Keywords(WIDTHS)], false),
+    ("border-r", &[Color, Keywords(WIDTHS)], false),
+    ("border-b", &[Color, Keywords(WIDTHS)], false),
+    ("border-l", &[Color, Keywords(WIDTHS)], false),
+    ("border-s", &[Color, Keywords(WIDTHS)], false),
+    ("border-e", &[Color, Keywords(WIDTHS)], false),
+    ("border-spacing", &[Spacing], false),
+    ("border-spacing-x", &[Spacing], false),
+    ("border-spacing-y", &[Spacing], false),
+    ("rounded", &[Keywords(RADII)], false),
+    ("rounded-t", &[Keywords(RADII)], false),
+    ("rounded-r", &[Keywords(RADII)], false),
+    ("rounded-b", &[Keywords(RADII)], false),
+    ("rounded-l", &[Keywords(RADII)], false),
+    ("rounded-s", &[Keywords(RADII)], false),
+    ("rounded-e", &[Keywords(RADII)], false),
+    ("rounded-tl", &[Keywords(RADII)], false),
+    ("rounded-tr", &[Keywords(RADII)], false),
+    ("rounded-br", &[Keywords(RADII)], false),
+    ("rounded-bl", &[Keywords(RADII)], false),
+    ("rounded-ss", &[Keywords(RADII)], false),
+    ("rounded-se", &[Keywords(RADII)], false),
+    ("rounded-es", &[Keywords(RADII)], false),
+    ("rounded-ee", &[Keywords(RADII)], false),
+    ("shadow", &[Color, Keywords(SHADOWS)], false),
+    ("opacity", &[Keywords(PERCENTS)], false),
+    // The opacity utilities of Tailwind v3, replaced by color modifiers in v4
+    ("bg-opacity", &[Keywords(PERCENTS)], false),
+    ("text-opacity", &[Keywords(PERCENTS)], false),
+    ("border-opacity", &[Keywords(PERCENTS)], false),
+    ("divide-opacity", &[Keywords(PERCENTS)], false),
+    ("ring-opacity", &[Keywords(PERCENTS)], false),
+    ("placeholder-opacity", &[Keywords(PERCENTS)], false),
+    ("z", &[Keywords(Z)], true),
+    ("leading", &[Keywords(LEADING)], false),
+    ("tracking", &[Keywords(TRACKING)], true),
+    ("grid-cols", &[Keywords(GRID)], false),
+    ("grid-rows", &[Keywords(GRID)], false),
+    ("grid-flow", &[Keywords(GRID_FLOW)], false),
+    ("auto-cols", &[Keywords(AUTO_TRACKS)], false),
+    ("auto-rows", &[Keywords(AUTO_TRACKS)], false),
+    ("col-span", &[Keywords(SPAN)], false),
+    ("col-start", &[Keywords(LINES)], false),
+    ("col-end", &[Keywords(LINES)], false),
+    ("row-span", &[Keywords(SPAN)], false),
+    ("row-start", &[Keywords(LINES)], false),
+    ("row-end", &[Keywords(LINES)], false),
+    ("col", &[Keywords(&["auto"])], false),
+    ("row", &[Keywords(&["auto"])], false),
+    ("order", &[Keywords(ORDER)], true),
+    ("flex", &[Keywords(FLEX)], false),
+    ("grow", &[Keywords(GROW)], false),
+    ("shrink", &[Keywords(GROW)], false),
+    ("duration", &[Keywords(DURATIONS)], false),
+    ("delay", &[Keywords(DURATIONS)], false),
+    ("ease", &[Keywords(EASE)], false),
+    ("transition", &[Keywords(TRANSITION)], false),
+    ("animate", &[Keywords(ANIMATE)], false),
+    ("scale", &[Keywords(SCALE)], true),
+    ("scale-x", &[Keywords(SCALE)], true),
+    ("scale-y", &[Keywords(SCALE)], true),
+    ("rotate", &[Keywords(ROTATE)], true),
+    ("skew-x", &[Keywords(SKEW)], true),
+    ("skew-y", &[Keywords(SKEW)], true),
+    ("origin", &[Keywords(POSITIONS)], false),
+    ("blur", &[Keywords(BLUR)], false),
+    ("backdrop-blur", &[Keywords(BLUR)], false),
+    ("drop-shadow", &[Keywords(SHADOWS)], false),
+    ("brightness", &[Keywords(FILTERS)], false),
+    ("contrast", &[Keywords(FILTERS)], false),
+    ("saturate", &[Keywords(FILTERS)], false),
+    ("backdrop-brightness", &[Keywords(FILTERS)], false),
+    ("backdrop-contrast", &[Keywords(FILTERS)], false),
+    ("backdrop-saturate", &[Keywords(FILTERS)], false),
+    ("backdrop-opacity", &[Keywords(PERCENTS)], false),
+    (
+        "hue-rotate",
+        &[Keywords(&["0", "15", "30", "60", "90", "180"])],
+        true,
+    ),
+    ("line-clamp", &[Keywords(LINE_CLAMP)], false),
+    ("columns", &[Keywords(COLUMNS)], false),
+    ("aspect", &[Keywords(ASPECT)], false),
+    ("object", &[Keywords(OBJECT)], false),
+    ("ring", &[Color, Keywords(RING)], false),
+    ("ring-offset", &[Color, Keywords(WIDTHS)], false),
+    ("outline", &[Color, Keywords(OUTLINE)], false),
+    ("outline-offset", &[Keywords(OFFSETS)], false),
+    (
+        "divide",
+        &[
+            Color,
+            Keywords(&["solid", "dashed", "dotted", "double", "none"]),
+        ],
+        false,
+    ),
+    ("divide-x", &[Keywords(DIVIDE)], false),
+    ("divide-y", &[Keywords(DIVIDE)], false),
+    ("decoration", &[Color, Keywords(DECORATION)], false),
+    ("underline-offset", &[Keywords(OFFSETS)], false),
+    ("from", &[Color, Keywords(STOPS)], false),
+    ("via", &[Color, Keywords(STOPS)], false),
+    ("to", &[Color, Keywords(STOPS)], false),
+    ("fill", &[Color, Keywords(FILL)], false),
+    ("stroke", &[Color, Keywords(STROKE)], false),
+    ("accent", &[Color, Keywords(&["auto"])], false),
+    ("caret", &[Color], false),
+    ("placeholder", &[Color], false),
+    ("cursor", &[Keywords(CURSOR)], false),
+    ("overflow", &[Keywords(OVERFLOW)], false),
+    ("overflow-x", &[Keywords(OVERFLOW)], false),
+    ("overflow-y", &[Keywords(OVERFLOW)], false),
+    (
+        "overscroll",
+        &[Keywords(&["auto", "contain", "none"])],
+        false,
+    ),
+    (
+        "overscroll-x",
+        &[Keywords(&["auto", "contain", "none"])],
+        false,
+    ),
+    (
+        "overscroll-y",
+        &[Keywords(&["auto", "contain", "none"])],
+        false,
+    ),
+    ("items", &[Keywords(ALIGN)], false),
+    ("justify", &[Keywords(ALIGN)], false),
+    ("justify-items", &[Keywords(ALIGN)], false),
+    ("justify-self", &[Keywords(ALIGN)], false),
+    ("content", &[Keywords(ALIGN), Keywords(CONTENT)], false),
+    ("self", &[Keywords(ALIGN)], false),
+    ("place-content", &[Keywords(ALIGN)], false),
+    ("place-items", &[Keywords(ALIGN)], false),
+    ("place-self", &[Keywords(ALIGN)], false),
+    ("whitespace", &[Keywords(WHITESPACE)], false),
+    ("break", &[Keywords(BREAK)], false),
+    ("break-after", &[Keywords(BREAK)], false),
+    ("break-before", &[Keywords(BREAK)], false),
+    ("break-inside", &[Keywords(BREAK)], false),
+    ("list", &[Keywords(LIST)], false),
+    ("select", &[Keywords(SELECT)], false),
+    ("pointer-events", &[Keywords(POINTER_EVENTS)], false),
+    ("resize", &[Keywords(RESIZE)], false),
+    ("float", &[Keywords(FLOAT)], false),
+    ("clear", &[Keywords(CLEAR)], false),
+    ("box", &[Keywords(BOX)], false),
+    ("mix-blend", &[Keywords(MIX_BLEND)], false),
+    ("bg-blend", &[Keywords(MIX_BLEND)], false),
+    ("will-change", &[Keywords(WILL_CHANGE)], false),
+    ("snap", &[Keywords(SNAP)], false),
+    ("touch", &[Keywords(TOUCH)], false),
+    ("table", &[Keywords(TABLE)], false),
+    ("caption", &[Keywords(&["top", "bottom"])], false),
+    ("hyphens", &[Keywords(HYPHENS)], false),
+    ("align", &[Keywords(VERTICAL_ALIGN)], false),
+    ("appearance", &[Keywords(APPEARANCE)], false),
+    ("scroll", &[Keywords(SCROLL)], false),
+    ("forced-color-adjust", &[Keywords(FORCED_COLOR)], false),
+    ("indent", &[Spacing], true),
+    ("isolation", &[Keywords(&["auto"])], false),
+    ("sr", &[Keywords(&["only"])], false),
+];
+
+/// Utilities that take no value.
+static STATIC: &[&str] = &[
+    "block",
+    "inline-block",
+    "inline",
+    "flex",
+    "inline-flex",
+    "grid",
+    "inline-grid",
+    "table",
+    "inline-table",
+    "contents",
+    "flow-root",
+    "list-item",
+    "hidden",
+    "static",
+    "fixed",
+    "absolute",
+    "relative",
+    "sticky",
+    "visible",
+    "invisible",
+    "collapse",
+    "grow",
+    "shrink",
+    "uppercase",
+    "lowercase",
+    "capitalize",
+    "normal-case",
+    "italic",
+    "not-italic",
+    "underline",
+    "overline",
+    "line-through",
+    "no-underline",
+    "truncate",
+    "antialiased",
+    "subpixel-antialiased",
+    "sr-only",
+    "not-sr-only",
+    "container",
+    "isolate",
+    "transform",
+    "transform-gpu",
+    "transform-cpu",
+    "transform-none",
+    "filter",
+    "filter-none",
+    "backdrop-filter",
+    "backdrop-filter-none",
+    "grayscale",
+    "grayscale-0",
+    "invert",
+    "invert-0",
+    "sepia",
+    "sepia-0",
+    "border",
+    "border-x",
+    "border-y",
+    "border-t",
+    "border-r",
+    "border-b",
+    "border-l",
+    "border-s",
+    "border-e",
+    "rounded",
+    "rounded-t",
+    "rounded-r",
+    "rounded-b",
+    "rounded-l",
+    "rounded-s",
+    "rounded-e",
+    "rounded-tl",
+    "rounded-tr",
+    "rounded-br",
+    "rounded-bl",
+    "rounded-ss",
+    "rounded-se",
+    "rounded-es",
+    "rounded-ee",
+    "shadow",
+    "ring",
+    "outline",
+    "blur",
+    "drop-shadow",
+    "transition",
+    "resize",
+    "divide-x",
+    "divide-y",
+    "ordinal",
+    "slashed-zero",
+    "lining-nums",
+    "oldstyle-nums",
+    "proportional-nums",
+    "tabular-nums",
+    "diagonal-fractions",
+    "stacked-fractions",
+    "normal-nums",
+    "group",
+    "peer",
+    "prose",
+];
+
+/// Variants that don't depend on the theme.
+static VARIANTS: &[&str] = &[
+    "hover",
+    "focus",
+    "focus-within",
+    "focus-visible",
+    "active",
+    "visited",
+    "target",
+    "first",
+    "last",
+    "only",
+    "odd",
+    "even",
+    "first-of-type",
+    "last-of-type",
+    "only-of-type",
+    "empty",
+    "disabled",
+    "enabled",
+    "checked",
+    "indeterminate",
+    "default",
+    "required",
+    "valid",
+    "invalid",
+    "user-valid",
+    "user-invalid",
+    "in-range",
+    "out-of-range",
+    "placeholder-shown",
+    "autofill",
+    "read-only",
+    "open",
+    "inert",
+    "before",
+    "after",
+    "first-letter",
+    "first-line",
+    "marker",
+    "selection",
+    "file",
+    "backdrop",
+    "placeholder",
+    "details-content",
+    "dark",
+    "portrait",
+    "landscape",
+    "motion-safe",
+    "motion-reduce",
+    "contrast-more",
+    "contrast-less",
+    "forced-colors",
+    "inverted-colors",
+    "pointer-fine",
+    "pointer-coarse",
+    "pointer-none",
+    "any-pointer-fine",
+    "any-pointer-coarse",
+    "any-pointer-none",
+    "noscript",
+    "print",
+    "rtl",
+    "ltr",
+    "starting",
+    "*",
+    "**",
+];
+static DEFAULT_SCREENS: &[&str] = &["sm", "md", "lg", "xl", "2xl"];
+
+/// Checks a single class, returning a description of the problem if it isn't valid.
+pub(crate) fn check(class: &str, theme: &Theme) -> Result<(), String> {
+    if theme.safelist.contains(class) {
+        return Ok(());
+    }
+    let mut segments = split_top_level(class, ':');
+    let utility = segments.pop().unwrap_or_default();
+    for variant in segments {
+        if !is_variant(variant, theme) {
+            return Err(unknown("variant", variant, &variant_candidates(theme)));
+        }
+    }
+
+    let utility = utility
+        .strip_prefix('!')
+        .or_else(|| utility.strip_suffix('!'))
+        .unwrap_or(utility);
+    let (negative, utility) = match utility.strip_prefix('-') {
+        Some(utility) => (true, utility),
+        None => (false, utility),
+    };
+    let utility = match &theme.prefix {
+        Some(prefix) => utility.strip_prefix(prefix.as_str()).ok_or_else(|| {
+            format!(
+                "Tailwind class `{}` is missing the configured prefix `{}`",
+                class, prefix
+            )
+        })?,
+        None => utility,
+    };
+    if utility.starts_with('[') && utility.ends_with(']') && utility.contains(':') {
+        // An arbitrary property like `[mask-type:luminance]`
+        return Ok(());
+    }
+    if !negative && STATIC.contains(&utility) {
+        return Ok(());
+    }
+    // Named groups and peers, e.g. `group/sidebar`
+    if let Some(("group" | "peer", name)) = utility.split_once('/') {
+        if !name.is_empty() {
+            return Ok(());
+        }
+    }
+
+    // The root is everything before one of the dashes, so try the longest roots first
+    let mut known_root = None;
+    for (index, _) in utility
+        .match_indices('-')
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+    {
+        let (root, value) = (&utility[..index], &utility[index + 1..]);
+        if let Some((_, kinds, allows_negative)) =
+            FUNCTIONAL.iter().find(|(name, _, _)| *name == root)
+        {
+            if negative && !allows_negative {
+                return Err(format!("Tailwind utility `{}` can't be negative", root));
+            }
+            if is_arbitrary(value) || kinds.iter().any(|kind| accepts(*kind, value, theme)) {
+                return Ok(());
+            }
+            known_root.get_or_insert((root, *kinds));
+        }
+    }
+    match known_root {
+        Some((root, kinds)) => {
+            let values: Vec<String> = kinds
+                .iter()
+                .flat_map(|kind| values(*kind, theme))
+                .map(|value| format!("{}-{}", root, value))
+                .collect();
+            Err(unknown("class", utility, &values))
+        }
+        None => Err(unknown("class", utility, &utility_candidates(theme))),
+    }
+}
+
+/// Splits a class at a separator that isn't inside brackets or parentheses.
+fn split_top_level(class: &str, separator: char) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let (mut depth, mut start) = (0i32, 0);
+    for (index, c) in class.char_indices() {
+        match c {
+            '[' | '(' => depth += 1,
+            ']' | ')' => depth -= 1,
+            c if c == separator && depth == 0 => {
+                segments.push(&class[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    segments.push(&class[start..]);
+    segments
+}
+
+fn is_arbitrary(value: &str) -> bool {
+    (value.starts_with('[') && value.ends_with(']'))
+        || (value.starts_with("(--") && value.ends_with(')'))
+}
+
+fn is_variant(variant: &str, theme: &Theme) -> bool {
+    let is_screen = |name: &str| DEFAULT_SCREENS.contains(&name) || theme.screens.contains(name);
+    if is_arbitrary(variant) || VARIANTS.contains(&variant) || is_screen(variant) {
+        return true;
+    }
+    if let Some(rest) = variant.strip_prefix("not-") {
+        return is_variant(rest, theme);
+    }
+    if let Some(rest) = variant
+        .strip_prefix("group-")
+        .or_else(|| variant.strip_prefix("peer-"))
+    {
+        // `group-hover/sidebar` targets a named group
+        let rest = rest.split('/').next().unwrap_or_default();
+        return is_arbitrary(rest) || VARIANTS.contains(&rest);
+    }
+    if let Some(rest) = variant
+        .strip_prefix("max-")
+        .or_else(|| variant.strip_prefix("min-"))
+    {
+        return is_arbitrary(rest) || is_screen(rest);
+    }
+    ["aria-", "data-", "supports-", "has-", "in-", "nth-"]
+        .iter()
+        .any(|prefix| {
+            variant
+                .strip_prefix(prefix)
+                .is_some_and(|rest| !rest.is_empty())
+        })
+}
+
+fn accepts(kind: Kind, value: &str, theme: &Theme) -> bool {
+    match kind {
+        Color => {
+            // Colors can have an opacity modifier, e.g. `bg-black/50`
+            let (color, opacity) = match value.split_once('/') {
+                Some((color, opacity)) => (color, Some(opacity)),
+                None => (value, None),
+            };
+            let valid_opacity = opacity
+                .map(|opacity| {
+                    is_arbitrary(opacity)
+                        || opacity.parse::<u8>().is_ok_and(|opacity| opacity <= 100)
+                })
+                .unwrap_or(true);
+            valid_opacity && is_color(color, theme)
+        }
+        Spacing => {
+            SPACING.contains(&value)
+                || theme.spacing.contains(value)
+                // Tailwind v4 derives spacing from a single step, so every multiple of it works
+                || (theme.v4
+                    && value
+                        .parse::<f64>()
+                        .is_ok_and(|value| value >= 0.0 && (value * 4.0).fract() == 0.0))
+        }
+        Fraction => FRACTIONS.contains(&value),
+        Font => FONTS.contains(&value) || theme.fonts.contains(value),
+        FontSize => {
+            let (size, line_height) = match value.split_once('/') {
+                Some((size, line_height)) => (size, Some(line_height)),
+                None => (value, None),
+            };
+            let valid_line_height = line_height
+                .map(|line_height| {
+                    is_arbitrary(line_height)
+                        || LEADING.contains(&line_height)
+                        || (theme.v4 && accepts(Spacing, line_height, theme))
+                })
+                .unwrap_or(true);
+            valid_line_height && FONT_SIZES.contains(&size)
+        }
+        Keywords(keywords) => keywords.contains(&value),
+    }
+}
+
+fn is_color(color: &str, theme: &Theme) -> bool {
+    if SPECIAL_COLORS.contains(&color) || theme.colors.contains(color) {
+        return true;
+    }
+    match color.rsplit_once('-') {
+        Some((name, shade)) => COLORS.contains(&name) && SHADES.contains(&shade),
+        None => false,
+    }
+}
+
+/// Lists the values of a kind, for suggestions.
+fn values(kind: Kind, theme: &Theme) -> Vec<String> {
+    let strings = |values: &[&str]| values.iter().map(|value| value.to_string()).collect();
+    match kind {
+        Color => COLORS
+            .iter()
+            .flat_map(|color| {
+                SHADES
+                    .iter()
+                    .map(move |shade| format!("{}-{}", color, shade))
+            })
+            .chain(SPECIAL_COLORS.iter().map(|color| color.to_string()))
+            .chain(theme.colors.iter().cloned())
+            .collect(),
+        Spacing => SPACING
+            .iter()
+            .map(|value| value.to_string())
+            .chain(theme.spacing.iter().cloned())
+            .collect(),
+        Fraction => strings(FRACTIONS),
+        Font => FONTS
+            .iter()
+            .map(|value| value.to_string())
+            .chain(theme.fonts.iter().cloned())
+            .collect(),
+        FontSize => strings(FONT_SIZES),
+        Keywords(keywords) => strings(keywords),
+    }
+}
+
+fn utility_candidates(theme: &Theme) -> Vec<String> {
+    let mut candidates: Vec<String> = STATIC.iter().map(|name| name.to_string()).collect();
+    for (root, kinds, _) in FUNCTIONAL {
+        for kind in kinds.iter() {
+            candidates.extend(
+                values(*kind, theme)
+                    .into_iter()
+                    .map(|value| format!("{}-{}", root, value)),
+            );
+        }
+    }
+    candidates
+}
+
+fn variant_candidates(theme: &Theme) -> Vec<String> {
+    VARIANTS
+        .iter()
+        .chain(DEFAULT_SCREENS)
+        .map(|name| name.to_string())
+        .chain(theme.screens.iter().cloned())
+        .collect()
+}
+
+/// Describes an unknown class or variant, suggesting the closest known one if there is one.
+fn unknown(what: &str, name: &str, candidates: &[String]) -> String {
+    let closest = candidates
+        .iter()
+        .map(|candidate| (strsim::levenshtein(name, candidate), candidate))
+        .min();
+    match closest {
+        Some((distance, candidate)) if distance <= (name.len() / 3).max(2) => format!(
+            "unknown Tailwind {} `{}`, did you mean `{}`?",
+            what, name, candidate
+        ),
+        _ => format!("unknown Tailwind {} `{}`", what, name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_classes() {
+        let theme = Theme::default();
+        let cases = [
+            ("px-4", true),
+            ("hover:bg-blue-500", true),
+            ("md:w-1/2", true),
+            ("-mt-2", true),
+            ("bg-black/50", true),
+            ("bg-bleu-500", false),
+            ("-p-2", false),
+            ("border-t", true),
+            ("border-b", true),
+            ("border-x", true),
+            ("border-r", true),
+            ("border-s", true),
+            ("border-t-2", true),
+            ("border-q", false),
+            ("rounded-t", true),
+            ("rounded-tl-lg", true),
+            ("scroll-mt-16", true),
+            ("-scroll-mt-16", true),
+            ("scroll-px-4", true),
+            ("-scroll-px-4", false),
+            ("scroll-mt-13", false),
+            ("bg-opacity-50", true),
+            ("text-opacity-75", true),
+            ("border-opacity-50", true),
+            ("ring-opacity-50", true),
+            ("bg-opacity-33", false),
+            ("text-sm", true),
+            ("text-sm/6", true),
+            ("text-lg/tight", true),
+            ("text-sm/[18px]", true),
+            ("text-sm/13", false),
+            ("text-left/6", false),
+            ("text-huge", false),
+            ("text-center", true),
+        ];
+        for (class, valid) in cases {
+            assert_eq!(check(class, &theme).is_ok(), valid, "{}", class);
+        }
+    }
+}
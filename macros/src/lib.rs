@@ -0,0 +1,69 @@
+//! Compile-time validated Tailwind class names for
+//! [perseus-tailwind](https://docs.rs/perseus-tailwind).
+//!
+//! Use this through the `macros` feature of `perseus-tailwind` rather than directly.
+
+mod grammar;
+mod theme;
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, LitStr};
+use theme::Theme;
+
+/// Checks a string of Tailwind classes at compile time and expands to it as a `&'static str`.
+///
+/// Every class is checked against Tailwind's utilities and variants and the project's theme, so a
+/// typo like `bg-bleu-500` fails the build with a suggestion instead of leaving an element
+/// unstyled. The theme is read from the `config` option (colors, spacing, font families, screens,
+/// prefix and safelist) in `Cargo.toml` metadata or `perseus-tailwind.toml`, and from the
+/// `@theme` variables in the input CSS file for Tailwind v4. Classes added by Tailwind plugins
+/// or a `tailwind.config.js` theme can be allowed by adding them to the `safelist`.
+///
+/// The class string stays a literal in the source, so Tailwind's content scanning always finds
+/// it.
+///
+/// ```
+/// # use perseus_tailwind_macros::tw;
+/// let classes: &'static str = tw!("px-4 py-2 hover:bg-blue-500 md:w-1/2");
+/// ```
+///
+/// ```compile_fail
+/// # use perseus_tailwind_macros::tw;
+/// // error: unknown Tailwind class `bg-bleu-500`, did you mean `bg-blue-500`?
+/// let classes = tw!("px-4 bg-bleu-500");
+/// ```
+#[proc_macro]
+pub fn tw(input: TokenStream) -> TokenStream {
+    let classes = parse_macro_input!(input as LitStr);
+    let theme = match Theme::load() {
+        Ok(theme) => theme,
+        Err(message) => {
+            return syn::Error::new(classes.span(), message)
+                .to_compile_error()
+                .into()
+        }
+    };
+
+    let value = classes.value();
+    let mut errors = value
+        .split_whitespace()
+        .filter_map(|class| grammar::check(class, &theme).err())
+        .map(|message| syn::Error::new(classes.span(), message));
+    if let Some(mut error) = errors.next() {
+        error.extend(errors);
+        // Each error is a separate `compile_error!`, so they need a block in expression position
+        let error = error.to_compile_error();
+        return quote!({ #error #classes }).into();
+    }
+
+    // Depending on the theme files makes the compiler re-run the macro when they change
+    let sources = theme.sources.iter().map(|path| path.display().to_string());
+    quote! {
+        {
+            #(const _: &[u8] = include_bytes!(#sources);)*
+            #classes
+        }
+    }
+    .into()
+}
@@ -0,0 +1,166 @@
+use std::{
+    collections::BTreeSet,
+    env, fs,
+    path::{Path, PathBuf},
+};
+use toml::{Table, Value};
+
+/// The parts of the project's Tailwind theme that decide which class names exist, read from the
+/// same places the plugin reads its options from.
+#[derive(Default)]
+pub(crate) struct Theme {
+    /// Custom colors, with shades as `name-shade`
+    pub(crate) colors: BTreeSet<String>,
+    pub(crate) spacing: BTreeSet<String>,
+    pub(crate) fonts: BTreeSet<String>,
+    pub(crate) screens: BTreeSet<String>,
+    /// Classes that are accepted as they are, e.g. ones added by Tailwind plugins
+    pub(crate) safelist: BTreeSet<String>,
+    pub(crate) prefix: Option<String>,
+    /// Whether the project uses Tailwind v4, which accepts any multiple of the spacing scale
+    pub(crate) v4: bool,
+    /// The files the theme was read from, so the macro can be re-run when they change
+    pub(crate) sources: Vec<PathBuf>,
+}
+
+impl Theme {
+    /// Loads the theme of the crate the macro is invoked in, from the `config` and `in_file`
+    /// options in `Cargo.toml` metadata and `perseus-tailwind.toml`, and the `@theme` variables
+    /// in the input CSS files.
+    pub(crate) fn load() -> Result<Self, String> {
+        let root = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap_or_default());
+        let mut theme = Theme::default();
+
+        let mut options = Table::new();
+        let manifest_path = root.join("Cargo.toml");
+        if let Some(manifest) = theme.read_table(&manifest_path)? {
+            let metadata = manifest
+                .get("package")
+                .and_then(|package| package.get("metadata"))
+                .and_then(|metadata| metadata.get("perseus-tailwind"));
+            if let Some(Value::Table(metadata)) = metadata {
+                merge(&mut options, metadata.clone());
+            }
+        }
+        if let Some(file) = theme.read_table(&root.join("perseus-tailwind.toml"))? {
+            merge(&mut options, file);
+        }
+
+        if let Some(Value::Table(config)) = options.get("config") {
+            theme.read_config(config);
+        }
+        theme.v4 = options
+            .get("major_version")
+            .and_then(Value::as_str)
+            .map(|version| version == "v4")
+            .unwrap_or(false);
+
+        let mut in_files = vec![options
+            .get("in_file")
+            .and_then(Value::as_str)
+            .unwrap_or("src/tailwind.css")
+            .to_string()];
+        if let Some(Value::Array(bundles)) = options.get("bundles") {
+            in_files.extend(
+                bundles
+                    .iter()
+                    .filter_map(|bundle| bundle.get("in_file")?.as_str())
+                    .map(String::from),
+            );
+        }
+        for in_file in in_files {
+            let path = root.join(in_file);
+            if let Ok(css) = fs::read_to_string(&path) {
+                theme.read_css(&css);
+                theme.sources.push(path);
+            }
+        }
+        Ok(theme)
+    }
+
+    fn read_table(&mut self, path: &Path) -> Result<Option<Table>, String> {
+        let Ok(contents) = fs::read_to_string(path) else {
+            return Ok(None);
+        };
+        self.sources.push(path.to_path_buf());
+        toml::from_str(&contents)
+            .map(Some)
+            .map_err(|err| format!("couldn't parse '{}': {}", path.display(), err))
+    }
+
+    /// Reads a [`TailwindConfig`](https://docs.rs/perseus-tailwind) in its serialized form.
+    fn read_config(&mut self, config: &Table) {
+        let keys = |name: &str| -> Vec<String> {
+            match config.get(name) {
+                Some(Value::Table(table)) => table.keys().cloned().collect(),
+                _ => Vec::new(),
+            }
+        };
+        if let Some(Value::Table(colors)) = config.get("colors") {
+            for (name, value) in colors {
+                match value {
+                    Value::Table(shades) => {
+                        for shade in shades.keys() {
+                            if shade == "DEFAULT" {
+                                self.colors.insert(name.clone());
+                            } else {
+                                self.colors.insert(format!("{}-{}", name, shade));
+                            }
+                        }
+                    }
+                    _ => {
+                        self.colors.insert(name.clone());
+                    }
+                }
+            }
+        }
+        self.spacing.extend(keys("spacing"));
+        self.fonts.extend(keys("font_family"));
+        self.screens.extend(keys("screens"));
+        if let Some(Value::Array(safelist)) = config.get("safelist") {
+            self.safelist
+                .extend(safelist.iter().filter_map(Value::as_str).map(String::from));
+        }
+        self.prefix = config
+            .get("prefix")
+            .and_then(Value::as_str)
+            .map(String::from);
+    }
+
+    /// Reads the theme variables of a v4 input file, e.g. `--color-brand: #0f766e;` in `@theme`.
+    fn read_css(&mut self, css: &str) {
+        if css.contains("@import \"tailwindcss\"") || css.contains("@import 'tailwindcss'") {
+            self.v4 = true;
+        }
+        for (index, _) in css.match_indices("--") {
+            let name: String = css[index + 2..]
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+                .collect();
+            let (set, value) = if let Some(value) = name.strip_prefix("color-") {
+                (&mut self.colors, value)
+            } else if let Some(value) = name.strip_prefix("spacing-") {
+                (&mut self.spacing, value)
+            } else if let Some(value) = name.strip_prefix("font-") {
+                (&mut self.fonts, value)
+            } else if let Some(value) = name.strip_prefix("breakpoint-") {
+                (&mut self.screens, value)
+            } else {
+                continue;
+            };
+            set.insert(value.to_string());
+        }
+    }
+}
+
+/// Merges `overrides` into `base` the way the plugin layers its option sources.
+fn merge(base: &mut Table, overrides: Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base)), Value::Table(overrides)) => merge(base, overrides),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
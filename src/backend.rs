@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+#[cfg(engine)]
+use {
+    crate::{
+        config, executable::ResolvedExecutable, init_tailwind, version, write_if_changed,
+        TailwindBundle, TailwindError, TailwindMajorVersion, TailwindMode, TailwindOptions,
+    },
+    std::{path::Path, time::Duration},
+};
+
+/// What generates the CSS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TailwindBackendKind {
+    /// The Tailwind CLI, configured with [`TailwindOptions::executable`](crate::TailwindOptions).
+    #[default]
+    Cli,
+    /// [railwind](https://docs.rs/railwind), a Tailwind-compatible generator that runs inside the
+    /// engine, so no Node or Tailwind binary is needed. Requires the `railwind` feature.
+    ///
+    /// Class names are taken from `view!` and `tw!` invocations in Rust sources and `class`
+    /// attributes in HTML files. Only Tailwind's default theme is supported, so
+    /// `tailwind.config.js` and [`TailwindConfig`](crate::TailwindConfig) are ignored.
+    Railwind,
+}
+
+/// Generates the CSS for bundles
+#[cfg(engine)]
+pub(crate) trait TailwindBackend: Sync {
+    /// Identifies the backend in fingerprints, so that switching or updating it rebuilds the CSS.
+    fn identity(&self) -> String;
+
+    /// Prepares building the bundles that are out of date, before any of them is built.
+    fn prepare(
+        &mut self,
+        _options: &TailwindOptions,
+        _bundles: &[TailwindBundle],
+    ) -> Result<(), TailwindError> {
+        Ok(())
+    }
+
+    /// Builds a single bundle. This is called for several bundles at once from different threads.
+    fn build(
+        &self,
+        options: &TailwindOptions,
+        bundle: &TailwindBundle,
+        mode: TailwindMode,
+    ) -> Result<(), TailwindError>;
+}
+
+#[cfg(engine)]
+impl TailwindBackendKind {
+    /// Creates the selected backend, which for the CLI means finding (or installing) it.
+    pub(crate) fn create(
+        self,
+        options: &TailwindOptions,
+        root: &Path,
+    ) -> Result<Box<dyn TailwindBackend>, TailwindError> {
+        match self {
+            TailwindBackendKind::Cli => Ok(Box::new(CliBackend::new(options, root)?)),
+            #[cfg(feature = "railwind")]
+            TailwindBackendKind::Railwind => Ok(Box::new(crate::railwind::RailwindBackend)),
+            #[cfg(not(feature = "railwind"))]
+            TailwindBackendKind::Railwind => Err(TailwindError::BackendUnavailable {
+                backend: "railwind".to_string(),
+                feature: "railwind".to_string(),
+            }),
+        }
+    }
+}
+
+/// Runs the Tailwind CLI for every bundle.
+#[cfg(engine)]
+struct CliBackend {
+    executable: ResolvedExecutable,
+    /// Detected in [`TailwindBackend::prepare`], so up-to-date builds don't start the CLI at all
+    major_version: TailwindMajorVersion,
+}
+
+#[cfg(engine)]
+impl CliBackend {
+    fn new(options: &TailwindOptions, root: &Path) -> Result<Self, TailwindError> {
+        let executable = match (
+            ResolvedExecutable::resolve(&options.executable, options.major_version, root),
+            &options.install,
+        ) {
+            (Err(TailwindError::BinaryNotFound { binary, .. }), Some(install)) => {
+                log::info!(
+                    "Tailwind CLI `{}` not found, installing the standalone CLI.",
+                    binary
+                );
+                ResolvedExecutable::from_path(install.install()?, root)
+            }
+            (resolved, _) => resolved?,
+        }
+        .with_timeout(options.timeout.map(Duration::from_secs));
+        Ok(Self {
+            executable,
+            major_version: options.major_version.unwrap_or(TailwindMajorVersion::V3),
+        })
+    }
+}
+
+#[cfg(engine)]
+impl TailwindBackend for CliBackend {
+    fn identity(&self) -> String {
+        self.executable.identity()
+    }
+
+    fn prepare(
+        &mut self,
+        options: &TailwindOptions,
+        bundles: &[TailwindBundle],
+    ) -> Result<(), TailwindError> {
+        let detected = match &options.version {
+            Some(requirement) => Some(version::check_version(&self.executable, requirement)?),
+            None => None,
+        };
+        self.major_version = match (options.major_version, detected) {
+            (Some(major_version), _) => major_version,
+            (None, Some(detected)) => TailwindMajorVersion::of(&detected),
+            (None, None) => TailwindMajorVersion::of(&version::detect_version(&self.executable)?),
+        };
+
+        if self.major_version == TailwindMajorVersion::V3
+            && bundles.iter().any(|bundle| bundle.config.is_none())
+        {
+            match &options.config {
+                Some(config) => {
+                    write_if_changed(&options.path(config::GENERATED_CONFIG), &config.render())?
+                }
+                None if !options.path("tailwind.config.js").exists() => init_tailwind(
+                    &options.path("tailwind.config.js"),
+                    &options.config_template,
+                )?,
+                None => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn build(
+        &self,
+        options: &TailwindOptions,
+        bundle: &TailwindBundle,
+        mode: TailwindMode,
+    ) -> Result<(), TailwindError> {
+        bundle.run(options, &self.executable, self.major_version, mode)
+    }
+}
@@ -0,0 +1,44 @@
+#![cfg(engine)]
+
+use crate::{try_run_tailwind, TailwindError, TailwindOptions};
+use perseus::engine::EngineOperation;
+use std::{
+    sync::Mutex,
+    thread::{self, JoinHandle},
+};
+
+/// The Tailwind run started by [`start`] that hasn't been joined yet.
+static PENDING: Mutex<Option<JoinHandle<Result<(), TailwindError>>>> = Mutex::new(None);
+
+/// Runs Tailwind on a background thread while Perseus builds the app.
+pub(crate) fn start(options: TailwindOptions, operation: EngineOperation) {
+    log::debug!("Building the CSS in the background.");
+    let handle = thread::spawn(move || try_run_tailwind(&options, operation));
+    let previous = PENDING
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .replace(handle);
+    if let Some(previous) = previous {
+        // Only one build runs per engine process, but don't lose an error if that changes
+        let _ = join_handle(previous);
+    }
+}
+
+/// Waits for the background run to finish, returning its error. Does nothing if none is running.
+pub(crate) fn join() -> Result<(), TailwindError> {
+    let handle = PENDING
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .take();
+    match handle {
+        Some(handle) => join_handle(handle),
+        None => Ok(()),
+    }
+}
+
+fn join_handle(handle: JoinHandle<Result<(), TailwindError>>) -> Result<(), TailwindError> {
+    match handle.join() {
+        Ok(result) => result,
+        Err(panic) => std::panic::resume_unwind(panic),
+    }
+}
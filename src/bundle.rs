@@ -0,0 +1,518 @@
+use serde::{Deserialize, Serialize};
+#[cfg(engine)]
+use {
+    crate::{
+        config, daemon, executable::ResolvedExecutable, extract, TailwindError,
+        TailwindMajorVersion, TailwindMode, TailwindOptions,
+    },
+    std::{
+        fs,
+        path::{Component, Path, PathBuf},
+    },
+};
+
+/// The target of the log records for the Tailwind CLI's output.
+#[cfg(engine)]
+static LOG_TARGET: &str = "perseus_tailwind";
+
+/// Directories that are never scanned, since they only hold build outputs and dependencies, even
+/// if a content glob like `**/*.rs` starts at the project root.
+#[cfg(engine)]
+static EXCLUDED_DIRS: [&str; 3] = ["dist", "target", "node_modules"];
+
+/// A CSS bundle built by the Tailwind CLI
+///
+/// Use these with [`TailwindOptions::bundles`](crate::TailwindOptions) to build several
+/// stylesheets with different content scopes from one plugin registration.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TailwindBundle {
+    /// A unique name for the bundle, used in log messages and the names of the files generated for
+    /// it, so it can't contain `/` or `\`
+    pub name: String,
+    /// The path to the input CSS file
+    pub in_file: String,
+    /// The path to the CSS file output by the CLI.\
+    /// **DO NOT PUT THIS IN `/static` UNLESS YOU LIKE BUILD LOOPS!**
+    pub out_file: String,
+    /// The Tailwind config to use for this bundle instead of `tailwind.config.js` (v3 only)
+    #[serde(default)]
+    pub config: Option<String>,
+    /// Content globs to scan for class names instead of the ones in the config, e.g.
+    /// `"./src/admin/**/*.rs"`
+    #[serde(default)]
+    pub content: Vec<String>,
+}
+
+impl TailwindBundle {
+    /// Creates a bundle that uses the default config and content.
+    pub fn new(
+        name: impl Into<String>,
+        in_file: impl Into<String>,
+        out_file: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            in_file: in_file.into(),
+            out_file: out_file.into(),
+            config: None,
+            content: Vec::new(),
+        }
+    }
+
+    /// The globs this bundle scans for class names, falling back to the config template's.
+    #[cfg(engine)]
+    pub(crate) fn content<'a>(&'a self, options: &'a TailwindOptions) -> &'a [String] {
+        if self.content.is_empty() {
+            &options.config_template.content
+        } else {
+            &self.content
+        }
+    }
+
+    /// The directories this bundle scans for class names, relative to the project root. They're
+    /// taken from everything that can set the content, whichever Tailwind version is used: the
+    /// bundle's `content`, the [`TailwindConfig`](crate::TailwindConfig), the `content` of the
+    /// config file and the `@source`s of the input CSS. Tailwind v4 also scans the whole project
+    /// unless the input imports it with `source(none)`.
+    #[cfg(engine)]
+    pub(crate) fn content_dirs(&self, options: &TailwindOptions) -> Vec<PathBuf> {
+        let mut globs = self.content.clone();
+        if self.config.is_none() {
+            if let Some(config) = &options.config {
+                globs.extend(config.content_globs());
+            }
+        }
+        let config_file = self.config.as_deref().into_iter().chain([
+            "tailwind.config.js",
+            "tailwind.config.cjs",
+            "tailwind.config.mjs",
+            "tailwind.config.ts",
+        ]);
+        if let Some(js) = config_file
+            .filter_map(|file| fs::read_to_string(options.path(file)).ok())
+            .next()
+        {
+            globs.extend(config::js_content(&js));
+        }
+
+        let css = fs::read_to_string(options.path(&self.in_file)).unwrap_or_default();
+        let css_dir = Path::new(&self.in_file)
+            .parent()
+            .unwrap_or_else(|| Path::new(""));
+        for source in config::css_sources(&css) {
+            globs.push(css_dir.join(source).to_string_lossy().into_owned());
+        }
+        let imports_v4 = css.lines().any(|line| {
+            let line = line.trim();
+            line.starts_with("@import")
+                && (line.contains("\"tailwindcss\"") || line.contains("'tailwindcss'"))
+                && !line.contains("source(none)")
+        });
+        if imports_v4 {
+            globs.push(".".to_string());
+        }
+
+        if globs.is_empty() {
+            globs = self.content(options).to_vec();
+        }
+        let mut dirs: Vec<PathBuf> = globs.iter().map(|glob| glob_base(glob)).collect();
+        dirs.sort();
+        dirs.dedup();
+        dirs
+    }
+
+    /// The files in the directories this bundle scans, in a stable order.
+    #[cfg(engine)]
+    pub(crate) fn content_files(
+        &self,
+        options: &TailwindOptions,
+    ) -> Result<Vec<PathBuf>, TailwindError> {
+        let excluded = EXCLUDED_DIRS.map(|dir| options.path(dir));
+        let mut files = Vec::new();
+        for dir in self.content_dirs(options) {
+            collect_files(&options.path(dir), &excluded, &mut files)?;
+        }
+        // Globs like `./**/*.rs` and `./src/**/*.rs` overlap
+        files.sort();
+        files.dedup();
+        Ok(files)
+    }
+
+    /// Runs the Tailwind CLI for this bundle.
+    #[cfg(engine)]
+    pub(crate) fn run(
+        &self,
+        options: &TailwindOptions,
+        executable: &ResolvedExecutable,
+        major_version: TailwindMajorVersion,
+        mode: TailwindMode,
+    ) -> Result<(), TailwindError> {
+        if major_version == TailwindMajorVersion::V4 {
+            if !options.path(&self.in_file).exists() {
+                self.init_v4(options)?;
+            } else if !self.content.is_empty() {
+                log::debug!(
+                    "Tailwind v4 reads sources from `@source` in '{}', `content` of bundle '{}' \
+                     is only used when generating that file.",
+                    self.in_file,
+                    self.name
+                );
+            }
+        }
+
+        let production = mode == TailwindMode::Production;
+        let mut in_file = self.in_file.clone();
+        let mut config = self.config.clone().or_else(|| {
+            options
+                .config
+                .as_ref()
+                .map(|_| config::GENERATED_CONFIG.to_string())
+        });
+        let mut content = self.content.clone();
+        if options.extract_classes {
+            let extracted = extract::prepare(self, options, major_version)?;
+            in_file = extracted.in_file;
+            if extracted.config.is_some() {
+                config = extracted.config;
+            }
+            if !content.is_empty() {
+                content.push(extracted.classes_file);
+            }
+        }
+        let content = content.join(",");
+        let mut args = vec!["-i", &in_file, "-o", &self.out_file];
+        match major_version {
+            TailwindMajorVersion::V3 => {
+                if let Some(config) = &config {
+                    args.extend(["-c", config]);
+                }
+                if !content.is_empty() {
+                    args.extend(["--content", &content]);
+                }
+            }
+            TailwindMajorVersion::V4 => {
+                if options.config.is_some() && self.config.is_none() {
+                    log::warn!(
+                        "Tailwind v4 is configured in CSS, ignoring the `TailwindConfig` for \
+                         bundle '{}'. Use `@theme` in '{}' instead.",
+                        self.name,
+                        self.in_file
+                    );
+                }
+                if self.config.is_some() {
+                    log::warn!(
+                        "Tailwind v4 is configured in CSS, ignoring `config` of bundle '{}'. Use \
+                         `@config` in '{}' instead.",
+                        self.name,
+                        self.in_file
+                    );
+                }
+            }
+        }
+        if options.minify.unwrap_or(production) {
+            args.push("--minify");
+        } else if production && major_version == TailwindMajorVersion::V4 {
+            args.push("--optimize");
+        }
+        if options.postcss {
+            match major_version {
+                TailwindMajorVersion::V3 => args.push("--postcss"),
+                TailwindMajorVersion::V4 => log::warn!(
+                    "Tailwind v4 doesn't support PostCSS processing, ignoring `postcss`."
+                ),
+            }
+        }
+        log::debug!(
+            "Building {:?} CSS for bundle '{}' with Tailwind {:?}.",
+            mode,
+            self.name,
+            major_version
+        );
+
+        if options.daemon && daemon::build(options, self, executable, &in_file, &args)? {
+            return Ok(());
+        }
+        let name = self.name.clone();
+        let output = executable.stream(&args, move |line| log_line(&name, line))?;
+        // Tailwind writes progress messages and warnings to stderr as well, so only the exit
+        // status tells us whether the build actually failed.
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let messages: Vec<&str> = stderr
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !is_warning(line))
+                .collect();
+            let errors = if messages.is_empty() {
+                stderr.to_string()
+            } else {
+                messages.join("\n")
+            };
+            return Err(TailwindError::from_failed_run(
+                output.status.code(),
+                &errors,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Generates a v4 input CSS file. Tailwind v4 is configured in CSS, so this takes the place
+    /// of `tailwind.config.js`. `@source` paths are relative to the CSS file itself.
+    #[cfg(engine)]
+    fn init_v4(&self, options: &TailwindOptions) -> Result<(), TailwindError> {
+        log::info!(
+            "Initializing Tailwind v4 in '{}' to search for class names in {}.",
+            self.in_file,
+            self.content(options).join(", ")
+        );
+        let relative_parent = Path::new(&self.in_file)
+            .parent()
+            .unwrap_or_else(|| Path::new(""));
+        let root = if relative_parent.is_absolute() {
+            options
+                .root
+                .as_deref()
+                .unwrap_or_else(|| Path::new("."))
+                .display()
+                .to_string()
+        } else {
+            let depth = relative_parent
+                .components()
+                .filter(|component| matches!(component, Component::Normal(_)))
+                .count();
+            if depth == 0 {
+                ".".to_string()
+            } else {
+                vec![".."; depth].join("/")
+            }
+        };
+        let mut css = "@import \"tailwindcss\";\n".to_string();
+        for glob in self.content(options) {
+            css.push_str(&format!(
+                "@source \"{}/{}\";\n",
+                root,
+                glob.trim_start_matches("./")
+            ));
+        }
+        let path = options.path(&self.in_file);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|source| TailwindError::Io {
+                path: parent.to_path_buf(),
+                source,
+            })?;
+        }
+        fs::write(&path, css).map_err(|source| TailwindError::Io { path, source })
+    }
+}
+
+/// Logs a line of the CLI's output under the [`LOG_TARGET`] as soon as it's written, with the
+/// bundle's name and, for the final `Done in 12ms.`, the build's duration as key-values.
+#[cfg(engine)]
+fn log_line(bundle: &str, line: &str) {
+    let line = line.trim();
+    if line.is_empty() {
+        return;
+    }
+    if let Some(duration_ms) = done_duration_ms(line) {
+        log::info!(target: LOG_TARGET, bundle, duration_ms; "[{}] {}", bundle, line);
+    } else if is_warning(line) {
+        log::warn!(target: LOG_TARGET, bundle; "[{}] {}", bundle, line);
+    } else if line.to_ascii_lowercase().contains("error") {
+        log::error!(target: LOG_TARGET, bundle; "[{}] {}", bundle, line);
+    } else {
+        log::debug!(target: LOG_TARGET, bundle; "[{}] {}", bundle, line);
+    }
+}
+
+/// Parses the duration from the `Done in 12ms.` line Tailwind prints after a build.
+#[cfg(engine)]
+fn done_duration_ms(line: &str) -> Option<u64> {
+    let duration = line.strip_prefix("Done in ")?.trim_end_matches('.');
+    match duration.strip_suffix("ms") {
+        Some(ms) => ms.trim().parse::<f64>().ok(),
+        None => duration
+            .strip_suffix('s')?
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .map(|s| s * 1000.0),
+    }
+    .map(|ms| ms.round() as u64)
+}
+
+/// Checks whether a line of Tailwind's stderr output is a warning rather than an error or a
+/// progress message.
+#[cfg(engine)]
+pub(crate) fn is_warning(line: &str) -> bool {
+    let lower = line.to_ascii_lowercase();
+    lower.starts_with("warn") || lower.contains("warning") || lower.starts_with("browserslist:")
+}
+
+/// The directory a glob starts matching in, i.e. everything before the first wildcard.
+#[cfg(engine)]
+fn glob_base(glob: &str) -> PathBuf {
+    Path::new(glob)
+        .components()
+        .take_while(|component| {
+            !component
+                .as_os_str()
+                .to_string_lossy()
+                .contains(['*', '?', '[', '{'])
+        })
+        .collect()
+}
+
+/// Recursively collects all files in a directory in a stable order, skipping the excluded
+/// directories and, like Tailwind does, hidden and git-ignored files. Symlinked directories aren't
+/// followed, so they can't loop.
+#[cfg(engine)]
+fn collect_files(
+    dir: &Path,
+    excluded: &[PathBuf],
+    files: &mut Vec<PathBuf>,
+) -> Result<(), TailwindError> {
+    if !dir.exists() {
+        return Ok(());
+    }
+    let excluded = excluded.to_vec();
+    let walker = ignore::WalkBuilder::new(dir)
+        // The project may not be a git repository yet
+        .require_git(false)
+        .sort_by_file_name(Ord::cmp)
+        .filter_entry(move |entry| !excluded.iter().any(|dir| entry.path() == dir))
+        .build();
+    for entry in walker {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => {
+                let message = err.to_string();
+                match err.into_io_error() {
+                    Some(source) => {
+                        return Err(TailwindError::Io {
+                            path: dir.to_path_buf(),
+                            source,
+                        })
+                    }
+                    // E.g. an invalid pattern in a `.gitignore`
+                    None => {
+                        log::warn!("Scanning '{}': {}", dir.display(), message);
+                        continue;
+                    }
+                }
+            }
+        };
+        let is_dir = entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_dir());
+        if !is_dir && entry.path().is_file() {
+            files.push(entry.into_path());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(all(test, engine))]
+mod tests {
+    use super::*;
+
+    fn options(root: &Path) -> TailwindOptions {
+        TailwindOptions {
+            root: Some(root.to_path_buf()),
+            ..Default::default()
+        }
+    }
+
+    fn write(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn content_dirs_merges_every_source_of_content() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            &dir.path().join("tailwind.config.js"),
+            "module.exports = { content: ['./src/**/*.rs', \"./templates/*.html\"] };",
+        );
+        write(
+            &dir.path().join("styles/in.css"),
+            "@tailwind base;\n@source \"../pages\";\n",
+        );
+        let mut bundle = TailwindBundle::new("app", "styles/in.css", "dist/app.css");
+        bundle.content = vec!["./admin/**/*.rs".to_string(), "./src/*.rs".to_string()];
+
+        assert_eq!(
+            bundle.content_dirs(&options(dir.path())),
+            [
+                PathBuf::from("./admin"),
+                PathBuf::from("./src"),
+                PathBuf::from("./templates"),
+                PathBuf::from("styles/../pages"),
+            ]
+        );
+    }
+
+    #[test]
+    fn content_dirs_include_the_project_for_v4_unless_disabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = options(dir.path());
+        let bundle = TailwindBundle::new("app", "in.css", "dist/app.css");
+
+        write(&dir.path().join("in.css"), "@import \"tailwindcss\";\n");
+        assert_eq!(bundle.content_dirs(&options), [PathBuf::from(".")]);
+
+        write(
+            &dir.path().join("in.css"),
+            "@import 'tailwindcss' source(none);\n@source \"./src\";\n",
+        );
+        assert_eq!(bundle.content_dirs(&options), [PathBuf::from("./src")]);
+    }
+
+    #[test]
+    fn content_dirs_fall_back_to_the_template_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = TailwindBundle::new("app", "in.css", "dist/app.css");
+
+        assert_eq!(
+            bundle.content_dirs(&options(dir.path())),
+            [PathBuf::from("./src"), PathBuf::from("./static")]
+        );
+    }
+
+    #[test]
+    fn content_files_skip_hidden_ignored_and_excluded_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(&root.join("in.css"), "@import \"tailwindcss\";\n");
+        write(&root.join(".gitignore"), "generated/\n");
+        for file in [
+            "src/main.rs",
+            "src/pages/index.rs",
+            ".git/HEAD",
+            ".cache/page.rs",
+            "generated/page.rs",
+            "dist/app.css",
+            "target/debug/app.rs",
+            "node_modules/pkg/index.js",
+        ] {
+            write(&root.join(file), "");
+        }
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(root, root.join("src/loop")).unwrap();
+        let bundle = TailwindBundle::new("app", "in.css", "dist/app.css");
+
+        let files = bundle.content_files(&options(root)).unwrap();
+        let files: Vec<_> = files
+            .iter()
+            .map(|file| file.strip_prefix(root.join(".")).unwrap())
+            .collect();
+        assert_eq!(
+            files,
+            [
+                Path::new("in.css"),
+                Path::new("src/main.rs"),
+                Path::new("src/pages/index.rs"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,640 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Content globs that cover all Rust files in `src` and all HTML files in `static`.
+pub static DEFAULT_CONTENT: &[&str] = &["./src/**/*.rs", "./static/**/*.html"];
+
+/// Values for the `tailwind.config.js` generated when a project doesn't have one yet
+///
+/// This only affects the first build, once the file exists it is never touched again. Also used
+/// for the `@source` directives of the input CSS generated for Tailwind v4.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfigTemplate {
+    /// Globs of the files Tailwind scans for class names
+    pub content: Vec<String>,
+    /// How dark mode variants are activated. Uses Tailwind's default if not set.
+    pub dark_mode: Option<DarkMode>,
+    /// A JavaScript object literal that becomes `theme.extend`, e.g.
+    /// `"{ colors: { brand: '#0f766e' } }"`
+    pub theme_extend: String,
+    /// Tailwind plugins to load, as package names passed to `require`, e.g.
+    /// `"@tailwindcss/typography"`
+    pub plugins: Vec<String>,
+}
+
+impl Default for ConfigTemplate {
+    fn default() -> Self {
+        Self {
+            content: DEFAULT_CONTENT
+                .iter()
+                .map(|glob| glob.to_string())
+                .collect(),
+            dark_mode: None,
+            theme_extend: "{}".to_string(),
+            plugins: Vec::new(),
+        }
+    }
+}
+
+/// Tailwind's dark mode strategies
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DarkMode {
+    /// Follow the operating system's preference (`prefers-color-scheme`)
+    Media,
+    /// Enable dark mode when an ancestor has the `dark` class
+    Class,
+    /// Enable dark mode when an ancestor matches this selector, e.g. `[data-theme="dark"]`
+    Selector(String),
+}
+
+impl ConfigTemplate {
+    /// Renders the `tailwind.config.js` template with these values.
+    #[cfg(engine)]
+    pub(crate) fn render(&self) -> String {
+        let content = self
+            .content
+            .iter()
+            .map(|glob| js_string(glob))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let dark_mode = match &self.dark_mode {
+            None => String::new(),
+            Some(DarkMode::Media) => "\n    darkMode: \"media\",".to_string(),
+            Some(DarkMode::Class) => "\n    darkMode: \"class\",".to_string(),
+            Some(DarkMode::Selector(selector)) => {
+                format!("\n    darkMode: [\"selector\", {}],", js_string(selector))
+            }
+        };
+        let plugins = self
+            .plugins
+            .iter()
+            .map(|plugin| format!("require({})", js_string(plugin)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        include_str!("default-config.js")
+            .replace("{{content}}", &format!("[{}]", content))
+            .replace("{{dark_mode}}", &dark_mode)
+            .replace("{{theme_extend}}", self.theme_extend.trim())
+            .replace("{{plugins}}", &plugins)
+    }
+}
+
+/// Quotes a string as a JavaScript string literal.
+#[cfg(engine)]
+pub(crate) fn js_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            // Line terminators in string literals before ES2019
+            '\u{2028}' => quoted.push_str("\\u2028"),
+            '\u{2029}' => quoted.push_str("\\u2029"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// The string literals in the `content` of a `tailwind.config.js`, e.g. `content: ["./src/**/*.rs"]`
+/// or `content: { files: ["./src/**/*.rs"] }`. This doesn't evaluate the config, so globs that are
+/// computed aren't found.
+#[cfg(engine)]
+pub(crate) fn js_content(config: &str) -> Vec<String> {
+    let Some(start) = config.find("content") else {
+        return Vec::new();
+    };
+    let rest = &config[start..];
+    let Some(open) = rest.find('[') else {
+        return Vec::new();
+    };
+    let mut globs = Vec::new();
+    let mut chars = rest[open + 1..].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            ']' => break,
+            '"' | '\'' | '`' => {
+                let mut glob = String::new();
+                while let Some(next) = chars.next() {
+                    match next {
+                        '\\' => glob.extend(chars.next()),
+                        next if next == c => break,
+                        next => glob.push(next),
+                    }
+                }
+                globs.push(glob);
+            }
+            _ => {}
+        }
+    }
+    globs
+}
+
+/// The paths of the `@source` directives in a Tailwind v4 input CSS file, relative to the file.
+#[cfg(engine)]
+pub(crate) fn css_sources(css: &str) -> Vec<String> {
+    css.lines()
+        .filter_map(|line| line.trim().strip_prefix("@source"))
+        .filter(|source| source.starts_with(char::is_whitespace))
+        .filter_map(|source| {
+            let source = source.trim_start();
+            let quote = source.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+            let path = &source[1..];
+            path.find(quote).map(|end| path[..end].to_string())
+        })
+        .collect()
+}
+
+/// The path the config generated from a [`TailwindConfig`] is written to. It lives in `dist`
+/// so it doesn't trigger rebuilds, and Node still finds the project's `node_modules` for plugins.
+#[cfg(engine)]
+pub(crate) static GENERATED_CONFIG: &str = "dist/tailwind.config.cjs";
+
+/// A Tailwind configuration defined in Rust
+///
+/// When this is set on [`TailwindOptions::config`](crate::TailwindOptions), it is written to a
+/// generated config file that is passed to the CLI on every run, so the project doesn't need a
+/// `tailwind.config.js` at all. Theme values extend Tailwind's default theme.
+///
+/// ```
+/// # use perseus_tailwind::{DarkMode, TailwindConfig};
+/// let config = TailwindConfig::new()
+///     .color("brand", "#0f766e")
+///     .color_scale("accent", [("100", "#fef3c7"), ("500", "#f59e0b")])
+///     .font_family("sans", ["Inter", "sans-serif"])
+///     .screen("3xl", "1920px")
+///     .dark_mode(DarkMode::Class)
+///     .safelist("bg-red-500")
+///     .plugin("@tailwindcss/typography");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TailwindConfig {
+    content: Vec<String>,
+    colors: BTreeMap<String, Color>,
+    spacing: BTreeMap<String, String>,
+    font_family: BTreeMap<String, Vec<String>>,
+    screens: BTreeMap<String, String>,
+    dark_mode: Option<DarkMode>,
+    safelist: Vec<String>,
+    plugins: Vec<String>,
+    prefix: Option<String>,
+    important: Option<Important>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum Color {
+    Single(String),
+    Scale(BTreeMap<String, String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum Important {
+    All(bool),
+    Selector(String),
+}
+
+impl TailwindConfig {
+    /// Creates an empty config, which scans [`DEFAULT_CONTENT`] and uses the default theme.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a glob of files to scan for class names. If none are added, [`DEFAULT_CONTENT`] is
+    /// used.
+    pub fn content(mut self, glob: impl Into<String>) -> Self {
+        self.content.push(glob.into());
+        self
+    }
+
+    /// Adds a color, e.g. `color("brand", "#0f766e")` for `bg-brand`.
+    pub fn color(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.colors.insert(name.into(), Color::Single(value.into()));
+        self
+    }
+
+    /// Adds a color with shades, e.g. `color_scale("brand", [("500", "#0f766e")])` for
+    /// `bg-brand-500`.
+    pub fn color_scale<K, V>(
+        mut self,
+        name: impl Into<String>,
+        shades: impl IntoIterator<Item = (K, V)>,
+    ) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let shades = shades
+            .into_iter()
+            .map(|(shade, value)| (shade.into(), value.into()))
+            .collect();
+        self.colors.insert(name.into(), Color::Scale(shades));
+        self
+    }
+
+    /// Adds a spacing value, e.g. `spacing("128", "32rem")` for `p-128`.
+    pub fn spacing(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.spacing.insert(name.into(), value.into());
+        self
+    }
+
+    /// Adds a font family stack, e.g. `font_family("display", ["Oswald", "sans-serif"])` for
+    /// `font-display`.
+    pub fn font_family<S: Into<String>>(
+        mut self,
+        name: impl Into<String>,
+        fonts: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.font_family
+            .insert(name.into(), fonts.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Adds a breakpoint, e.g. `screen("3xl", "1920px")` for `3xl:flex`.
+    pub fn screen(mut self, name: impl Into<String>, min_width: impl Into<String>) -> Self {
+        self.screens.insert(name.into(), min_width.into());
+        self
+    }
+
+    /// Sets how dark mode variants are activated.
+    pub fn dark_mode(mut self, dark_mode: DarkMode) -> Self {
+        self.dark_mode = Some(dark_mode);
+        self
+    }
+
+    /// Always generates this class, even if it isn't found in the content.
+    pub fn safelist(mut self, class: impl Into<String>) -> Self {
+        self.safelist.push(class.into());
+        self
+    }
+
+    /// Loads a Tailwind plugin by its package name, e.g. `"@tailwindcss/forms"`.
+    pub fn plugin(mut self, package: impl Into<String>) -> Self {
+        self.plugins.push(package.into());
+        self
+    }
+
+    /// Prefixes all utilities, e.g. `prefix("tw-")` for `tw-flex`.
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Marks all utilities as `!important`.
+    pub fn important(mut self, important: bool) -> Self {
+        self.important = Some(Important::All(important));
+        self
+    }
+
+    /// Scopes all utilities under a selector to increase their specificity, e.g. `"#app"`.
+    pub fn important_selector(mut self, selector: impl Into<String>) -> Self {
+        self.important = Some(Important::Selector(selector.into()));
+        self
+    }
+
+    /// The globs of the files Tailwind scans with this config.
+    #[cfg(engine)]
+    pub(crate) fn content_globs(&self) -> Vec<String> {
+        if self.content.is_empty() {
+            DEFAULT_CONTENT
+                .iter()
+                .map(|glob| glob.to_string())
+                .collect()
+        } else {
+            self.content.clone()
+        }
+    }
+
+    /// Renders the config as a CommonJS module.
+    #[cfg(engine)]
+    pub(crate) fn render(&self) -> String {
+        let strings = |values: &[String]| Js::Array(values.iter().cloned().map(Js::Str).collect());
+        let string_map = |values: &BTreeMap<String, String>| {
+            Js::Object(
+                values
+                    .iter()
+                    .map(|(key, value)| (key.clone(), Js::Str(value.clone())))
+                    .collect(),
+            )
+        };
+
+        let content = if self.content.is_empty() {
+            Js::Array(
+                DEFAULT_CONTENT
+                    .iter()
+                    .map(|glob| Js::Str(glob.to_string()))
+                    .collect(),
+            )
+        } else {
+            strings(&self.content)
+        };
+        let mut config = vec![("content".to_string(), content)];
+        if let Some(dark_mode) = &self.dark_mode {
+            let value = match dark_mode {
+                DarkMode::Media => Js::Str("media".to_string()),
+                DarkMode::Class => Js::Str("class".to_string()),
+                DarkMode::Selector(selector) => Js::Array(vec![
+                    Js::Str("selector".to_string()),
+                    Js::Str(selector.clone()),
+                ]),
+            };
+            config.push(("darkMode".to_string(), value));
+        }
+        if let Some(prefix) = &self.prefix {
+            config.push(("prefix".to_string(), Js::Str(prefix.clone())));
+        }
+        match &self.important {
+            Some(Important::All(important)) => {
+                config.push(("important".to_string(), Js::Bool(*important)))
+            }
+            Some(Important::Selector(selector)) => {
+                config.push(("important".to_string(), Js::Str(selector.clone())))
+            }
+            None => {}
+        }
+        if !self.safelist.is_empty() {
+            config.push(("safelist".to_string(), strings(&self.safelist)));
+        }
+
+        let mut extend = Vec::new();
+        if !self.colors.is_empty() {
+            let colors = self
+                .colors
+                .iter()
+                .map(|(name, color)| {
+                    let value = match color {
+                        Color::Single(value) => Js::Str(value.clone()),
+                        Color::Scale(shades) => string_map(shades),
+                    };
+                    (name.clone(), value)
+                })
+                .collect();
+            extend.push(("colors".to_string(), Js::Object(colors)));
+        }
+        if !self.spacing.is_empty() {
+            extend.push(("spacing".to_string(), string_map(&self.spacing)));
+        }
+        if !self.font_family.is_empty() {
+            let fonts = self
+                .font_family
+                .iter()
+                .map(|(name, fonts)| (name.clone(), strings(fonts)))
+                .collect();
+            extend.push(("fontFamily".to_string(), Js::Object(fonts)));
+        }
+        if !self.screens.is_empty() {
+            extend.push(("screens".to_string(), string_map(&self.screens)));
+        }
+        config.push((
+            "theme".to_string(),
+            Js::Object(vec![("extend".to_string(), Js::Object(extend))]),
+        ));
+        config.push((
+            "plugins".to_string(),
+            Js::Array(
+                self.plugins
+                    .iter()
+                    .map(|plugin| Js::Raw(format!("require({})", js_string(plugin))))
+                    .collect(),
+            ),
+        ));
+
+        format!(
+            "// Generated by perseus-tailwind from the `TailwindConfig` of your Perseus app, don't \
+             edit.\n/** @type {{import('tailwindcss').Config}} */\nmodule.exports = {};\n",
+            Js::Object(config).render(0)
+        )
+    }
+}
+
+/// A minimal JavaScript value, just enough to render configs.
+#[cfg(engine)]
+enum Js {
+    Str(String),
+    Bool(bool),
+    Raw(String),
+    Array(Vec<Js>),
+    Object(Vec<(String, Js)>),
+}
+
+#[cfg(engine)]
+impl Js {
+    fn render(&self, indent: usize) -> String {
+        let pad = "    ".repeat(indent + 1);
+        let end = "    ".repeat(indent);
+        match self {
+            Js::Str(value) => js_string(value),
+            Js::Bool(value) => value.to_string(),
+            Js::Raw(value) => value.clone(),
+            Js::Array(values) if values.is_empty() => "[]".to_string(),
+            Js::Array(values) => format!(
+                "[\n{}\n{}]",
+                values
+                    .iter()
+                    .map(|value| format!("{}{},", pad, value.render(indent + 1)))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                end
+            ),
+            Js::Object(entries) if entries.is_empty() => "{}".to_string(),
+            Js::Object(entries) => format!(
+                "{{\n{}\n{}}}",
+                entries
+                    .iter()
+                    .map(|(key, value)| format!(
+                        "{}{}: {},",
+                        pad,
+                        js_string(key),
+                        value.render(indent + 1)
+                    ))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                end
+            ),
+        }
+    }
+}
+
+#[cfg(all(test, engine))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_js_strings() {
+        assert_eq!(js_string("./src/**/*.rs"), r#""./src/**/*.rs""#);
+        assert_eq!(
+            js_string("a\"b\\c\nd\re\u{2028}f\u{2029}g"),
+            r#""a\"b\\c\nd\re\u2028f\u2029g""#
+        );
+    }
+
+    #[test]
+    fn renders_the_default_template() {
+        assert_eq!(
+            ConfigTemplate::default().render(),
+            r#"/** @type {import('tailwindcss').Config} */
+module.exports = {
+    content: ["./src/**/*.rs", "./static/**/*.html"],
+    theme: {
+        extend: {},
+    },
+    plugins: [],
+}
+"#
+        );
+    }
+
+    #[test]
+    fn renders_a_customized_template() {
+        let template = ConfigTemplate {
+            content: vec!["./src/**/*.rs".to_string()],
+            dark_mode: Some(DarkMode::Selector(r#"[data-theme="dark"]"#.to_string())),
+            theme_extend: " { colors: { brand: '#0f766e' } } ".to_string(),
+            plugins: vec![
+                "@tailwindcss/forms".to_string(),
+                "@tailwindcss/typography".to_string(),
+            ],
+        };
+        assert_eq!(
+            template.render(),
+            r#"/** @type {import('tailwindcss').Config} */
+module.exports = {
+    content: ["./src/**/*.rs"],
+    darkMode: ["selector", "[data-theme=\"dark\"]"],
+    theme: {
+        extend: { colors: { brand: '#0f766e' } },
+    },
+    plugins: [require("@tailwindcss/forms"), require("@tailwindcss/typography")],
+}
+"#
+        );
+    }
+
+    #[test]
+    fn renders_an_empty_config() {
+        assert_eq!(
+            TailwindConfig::new().render(),
+            r#"// Generated by perseus-tailwind from the `TailwindConfig` of your Perseus app, don't edit.
+/** @type {import('tailwindcss').Config} */
+module.exports = {
+    "content": [
+        "./src/**/*.rs",
+        "./static/**/*.html",
+    ],
+    "theme": {
+        "extend": {},
+    },
+    "plugins": [],
+};
+"#
+        );
+    }
+
+    #[test]
+    fn renders_a_full_config() {
+        let config = TailwindConfig::new()
+            .content("./src/**/*.rs")
+            .color("brand", "#0f766e")
+            .color_scale("accent", [("100", "#fef3c7"), ("500", "#f59e0b")])
+            .spacing("128", "32rem")
+            .font_family("sans", ["Inter", "sans-serif"])
+            .screen("3xl", "1920px")
+            .dark_mode(DarkMode::Selector(r#"[data-theme="dark"]"#.to_string()))
+            .safelist("bg-red-500")
+            .plugin("@tailwindcss/forms")
+            .plugin("@tailwindcss/typography")
+            .prefix("tw-")
+            .important_selector("#app");
+        assert_eq!(
+            config.render(),
+            r##"// Generated by perseus-tailwind from the `TailwindConfig` of your Perseus app, don't edit.
+/** @type {import('tailwindcss').Config} */
+module.exports = {
+    "content": [
+        "./src/**/*.rs",
+    ],
+    "darkMode": [
+        "selector",
+        "[data-theme=\"dark\"]",
+    ],
+    "prefix": "tw-",
+    "important": "#app",
+    "safelist": [
+        "bg-red-500",
+    ],
+    "theme": {
+        "extend": {
+            "colors": {
+                "accent": {
+                    "100": "#fef3c7",
+                    "500": "#f59e0b",
+                },
+                "brand": "#0f766e",
+            },
+            "spacing": {
+                "128": "32rem",
+            },
+            "fontFamily": {
+                "sans": [
+                    "Inter",
+                    "sans-serif",
+                ],
+            },
+            "screens": {
+                "3xl": "1920px",
+            },
+        },
+    },
+    "plugins": [
+        require("@tailwindcss/forms"),
+        require("@tailwindcss/typography"),
+    ],
+};
+"##
+        );
+    }
+
+    #[test]
+    fn renders_simple_dark_modes_and_important() {
+        let rendered = TailwindConfig::new()
+            .dark_mode(DarkMode::Class)
+            .important(true)
+            .render();
+        assert!(rendered.contains("\n    \"darkMode\": \"class\",\n"));
+        assert!(rendered.contains("\n    \"important\": true,\n"));
+    }
+
+    #[test]
+    fn finds_the_content_of_config_files() {
+        assert_eq!(
+            js_content(
+                "module.exports = {\n  content: ['./src/**/*.rs', \"./static/*.html\", `./it\\'s`],\n  \
+                 theme: { extend: {} },\n};"
+            ),
+            ["./src/**/*.rs", "./static/*.html", "./it's"]
+        );
+        assert_eq!(
+            js_content("export default { content: { files: [\"./src/**/*.rs\"], extract: {} } }"),
+            ["./src/**/*.rs"]
+        );
+        assert!(js_content("module.exports = { theme: {} }").is_empty());
+    }
+
+    #[test]
+    fn finds_the_sources_of_input_css() {
+        let css = "@import \"tailwindcss\" source(none);\n\
+                   @source \"../templates\";\n\
+                   \t@source './src/**/*.rs' ;\n\
+                   @sourcemap \"ignored\";\n\
+                   @source missing-quotes;\n";
+        assert_eq!(css_sources(css), ["../templates", "./src/**/*.rs"]);
+    }
+}
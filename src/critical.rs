@@ -0,0 +1,388 @@
+#![cfg(engine)]
+
+use crate::{manifest, paths, TailwindError, TailwindOptions};
+use scraper::{Html, Selector};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Where `perseus export` writes the pages, relative to the directory it runs in.
+static EXPORT_DIR: &str = "dist/exported";
+
+/// Marks the inlined styles, so a page is never processed twice.
+static MARKER: &str = "data-perseus-tailwind-critical";
+
+/// Pseudo-classes that only apply after user interaction, so their rules aren't needed for the
+/// first paint.
+static INTERACTIVE: &[&str] = &[
+    ":hover",
+    ":focus",
+    ":active",
+    ":visited",
+    ":target",
+    ":focus-visible",
+    ":focus-within",
+];
+
+/// Inlines the rules of the bundles' stylesheets that apply to each exported page and defers
+/// loading the full stylesheets.
+pub(crate) fn inline(options: &TailwindOptions) -> Result<(), TailwindError> {
+    let mut pages = Vec::new();
+    collect_pages(Path::new(EXPORT_DIR), &mut pages);
+    let stylesheets = stylesheets(options)?;
+    for page in pages {
+        let io_error = |source| TailwindError::Io {
+            path: page.clone(),
+            source,
+        };
+        let html = fs::read_to_string(&page).map_err(io_error)?;
+        if let Some(inlined) = inline_page(&html, &stylesheets) {
+            log::debug!("Inlined critical CSS into '{}'.", page.display());
+            fs::write(&page, inlined).map_err(io_error)?;
+        }
+    }
+    Ok(())
+}
+
+/// The bundles' output files by file name and their contents, including the hashed copies.
+fn stylesheets(options: &TailwindOptions) -> Result<Vec<(String, String)>, TailwindError> {
+    let mut stylesheets = Vec::new();
+    for bundle in paths::output_bundles(options)? {
+        let out_file = options.path(&bundle.out_file);
+        let hashed = manifest::hashed_name(&out_file).map(|name| out_file.with_file_name(name));
+        for path in [Some(out_file), hashed].into_iter().flatten() {
+            if let (Some(name), Ok(css)) = (path.file_name(), fs::read_to_string(&path)) {
+                stylesheets.push((name.to_string_lossy().into_owned(), css));
+            }
+        }
+    }
+    Ok(stylesheets)
+}
+
+fn collect_pages(dir: &Path, pages: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_pages(&path, pages);
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == "html")
+        {
+            pages.push(path);
+        }
+    }
+}
+
+/// Rewrites the `<link>`s to the bundles' stylesheets in a page, or returns `None` if there are
+/// none or the page was already processed.
+fn inline_page(html: &str, stylesheets: &[(String, String)]) -> Option<String> {
+    if html.contains(MARKER) {
+        return None;
+    }
+    let document = Html::parse_document(html);
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+    let mut changed = false;
+    while let Some(start) = rest.find("<link") {
+        let Some(end) = rest[start..].find('>').map(|end| start + end + 1) else {
+            break;
+        };
+        let tag = &rest[start..end];
+        output.push_str(&rest[..start]);
+        rest = &rest[end..];
+
+        let Some(href) = stylesheet_href(tag) else {
+            output.push_str(tag);
+            continue;
+        };
+        let name = href
+            .split(['?', '#'])
+            .next()
+            .and_then(|path| path.rsplit('/').next())
+            .unwrap_or_default();
+        let Some((_, css)) = stylesheets.iter().find(|(file, _)| file == name) else {
+            output.push_str(tag);
+            continue;
+        };
+        output.push_str(&format!(
+            "<style {}>{}</style>",
+            MARKER,
+            critical_css(css, &document)
+        ));
+        output.push_str(&format!(
+            r#"<link rel="preload" as="style" href="{0}" onload="this.onload=null;this.rel='stylesheet'"><noscript><link rel="stylesheet" href="{0}"></noscript>"#,
+            href.replace('&', "&amp;").replace('"', "&quot;")
+        ));
+        changed = true;
+    }
+    output.push_str(rest);
+    changed.then_some(output)
+}
+
+/// The `href` of a stylesheet `<link>` tag.
+fn stylesheet_href(tag: &str) -> Option<String> {
+    let fragment = Html::parse_fragment(tag);
+    let link = Selector::parse("link[rel=stylesheet][href]").ok()?;
+    let element = fragment.select(&link).next()?;
+    element.value().attr("href").map(str::to_string)
+}
+
+/// The rules of a stylesheet that apply to elements in the document. Rules whose selectors can't
+/// be checked are kept.
+fn critical_css(css: &str, document: &Html) -> String {
+    let mut critical = String::new();
+    for (prelude, body) in rules(css) {
+        let prelude = prelude.trim();
+        if let Some(at_rule) = prelude.strip_prefix('@') {
+            let name = at_rule
+                .split(|c: char| c.is_whitespace() || c == '(')
+                .next()
+                .unwrap_or_default();
+            match (name, body) {
+                // Conditional rules and layers are kept if any of their rules apply
+                ("media" | "supports" | "layer" | "container", Some(body)) => {
+                    let inner = critical_css(body, document);
+                    if !inner.is_empty() {
+                        critical.push_str(&format!("{}{{{}}}", prelude, inner));
+                    }
+                }
+                ("layer", None) => critical.push_str(&format!("{};", prelude)),
+                ("font-face" | "property", Some(body)) => {
+                    critical.push_str(&format!("{}{{{}}}", prelude, body))
+                }
+                // Animations and imports are left to the full stylesheet
+                _ => {}
+            }
+        } else if let Some(body) = body {
+            if applies(prelude, document) {
+                critical.push_str(&format!("{}{{{}}}", prelude, body));
+            }
+        }
+    }
+    critical
+}
+
+/// Whether any selector of a selector list matches an element in the document.
+fn applies(selectors: &str, document: &Html) -> bool {
+    split_top_level(selectors, ',').into_iter().any(|selector| {
+        if INTERACTIVE
+            .iter()
+            .any(|pseudo| contains_pseudo(selector, pseudo))
+        {
+            return false;
+        }
+        // Pseudo-elements style parts of the element they belong to
+        let selector = strip_pseudo_elements(selector);
+        let selector = selector.trim();
+        if selector.is_empty() {
+            return true;
+        }
+        let matches = match Selector::parse(selector) {
+            Ok(selector) => document.select(&selector).next().is_some(),
+            Err(_) => true,
+        };
+        matches
+    })
+}
+
+/// Checks for a pseudo-class that isn't part of a longer name or an escaped class name like
+/// `.hover\:underline`.
+fn contains_pseudo(selector: &str, pseudo: &str) -> bool {
+    selector.match_indices(pseudo).any(|(index, _)| {
+        let escaped = selector[..index].ends_with('\\');
+        let next = selector[index + pseudo.len()..].chars().next();
+        !escaped && !matches!(next, Some(c) if c.is_alphanumeric() || c == '-')
+    })
+}
+
+fn strip_pseudo_elements(selector: &str) -> String {
+    let mut stripped = String::with_capacity(selector.len());
+    let mut chars = selector.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        if c == '\\' {
+            stripped.push(c);
+            if let Some((_, escaped)) = chars.next() {
+                stripped.push(escaped);
+            }
+        } else if selector[index..].starts_with("::") {
+            chars.next();
+            while let Some((_, c)) = chars.peek() {
+                if c.is_alphanumeric() || *c == '-' {
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            // Skip arguments like `::part(label)`
+            if let Some((_, '(')) = chars.peek() {
+                for (_, c) in chars.by_ref() {
+                    if c == ')' {
+                        break;
+                    }
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+/// Splits a stylesheet into its top-level rules as preludes and blocks. Statements like
+/// `@import` have no block.
+fn rules(css: &str) -> Vec<(&str, Option<&str>)> {
+    let mut rules = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    let mut block_start = 0;
+    let mut quote = None;
+    let mut chars = css.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        match (quote, c) {
+            (_, '\\') => {
+                chars.next();
+            }
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '/') if css[index..].starts_with("/*") => {
+                let end = css[index + 2..]
+                    .find("*/")
+                    .map_or(css.len(), |end| index + end + 4);
+                while chars.peek().is_some_and(|(next, _)| *next < end) {
+                    chars.next();
+                }
+                if depth == 0 {
+                    start = end;
+                }
+            }
+            (None, '{') => {
+                if depth == 0 {
+                    block_start = index;
+                }
+                depth += 1;
+            }
+            (None, '}') if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    rules.push((&css[start..block_start], Some(&css[block_start + 1..index])));
+                    start = index + 1;
+                }
+            }
+            (None, ';') if depth == 0 => {
+                rules.push((&css[start..index], None));
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    rules
+}
+
+/// Splits at a separator outside of parentheses, brackets and strings.
+fn split_top_level(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    let mut quote = None;
+    let mut escaped = false;
+    for (index, c) in value.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match (quote, c) {
+            (_, '\\') => escaped = true,
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(' | '[') => depth += 1,
+            (None, ')' | ']') => depth -= 1,
+            (None, c) if c == separator && depth == 0 => {
+                parts.push(&value[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
+
+#[cfg(all(test, engine))]
+mod tests {
+    use super::*;
+
+    static PAGE: &str = r#"<html><head><link rel="icon" href="/favicon.ico"><link rel="stylesheet" href="/.perseus/static/tailwind.css?v=1&x=2"></head><body><div class="a md:flex hover:underline"></div></body></html>"#;
+
+    fn critical(css: &str) -> String {
+        critical_css(css, &Html::parse_document(PAGE))
+    }
+
+    #[test]
+    fn splits_rules_around_braces_in_comments_and_strings() {
+        let css = r#"/* .x { */ .a{content:"}"} @import "b.css"; .c{/* } */color:red}"#;
+        let rules: Vec<_> = rules(css)
+            .into_iter()
+            .map(|(prelude, body)| (prelude.trim(), body))
+            .collect();
+        assert_eq!(
+            rules,
+            [
+                (".a", Some(r#"content:"}""#)),
+                (r#"@import "b.css""#, None),
+                (".c", Some("/* } */color:red")),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_nested_rules_that_apply() {
+        let css = "@media (min-width:768px){@layer utilities{.a{color:red}.b{color:blue}}}\
+                   @layer base{.b{color:green}}@layer theme, base;";
+        assert_eq!(
+            critical(css),
+            "@media (min-width:768px){@layer utilities{.a{color:red}}}@layer theme, base;"
+        );
+    }
+
+    #[test]
+    fn matches_escaped_variants_but_not_interactive_states() {
+        let css = concat!(
+            r".md\:flex{display:flex}",
+            r".hover\:underline:hover{text-decoration:underline}",
+            r".sm\:grid{display:grid}",
+        );
+        assert_eq!(critical(css), r".md\:flex{display:flex}");
+    }
+
+    #[test]
+    fn keeps_pseudo_elements_of_matching_elements() {
+        let css = r#"*,::before,::after{box-sizing:border-box}.a::before{content:""}.b::after{content:""}"#;
+        assert_eq!(
+            critical(css),
+            r#"*,::before,::after{box-sizing:border-box}.a::before{content:""}"#
+        );
+    }
+
+    #[test]
+    fn replaces_stylesheet_links_with_inlined_css_and_a_preload() {
+        let stylesheets = [(
+            "tailwind.css".to_string(),
+            ".a{color:red}.b{color:blue}".to_string(),
+        )];
+        let page = inline_page(PAGE, &stylesheets).unwrap();
+
+        assert!(page.contains(r#"<link rel="icon" href="/favicon.ico">"#));
+        assert!(page.contains(&format!("<style {}>.a{{color:red}}</style>", MARKER)));
+        assert!(page.contains(
+            r#"<link rel="preload" as="style" href="/.perseus/static/tailwind.css?v=1&amp;x=2" onload="this.onload=null;this.rel='stylesheet'"><noscript><link rel="stylesheet" href="/.perseus/static/tailwind.css?v=1&amp;x=2"></noscript>"#
+        ));
+        assert!(!page
+            .contains(r#"<link rel="stylesheet" href="/.perseus/static/tailwind.css?v=1&x=2">"#));
+
+        assert_eq!(inline_page(&page, &stylesheets), None);
+        assert_eq!(inline_page(PAGE, &[]), None);
+    }
+}
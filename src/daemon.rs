@@ -0,0 +1,275 @@
+#![cfg(engine)]
+
+use crate::{executable::ResolvedExecutable, TailwindBundle, TailwindError, TailwindOptions};
+use std::path::Path;
+#[cfg(unix)]
+use {
+    crate::{bundle, hex},
+    sha2::{Digest, Sha256},
+    std::{
+        fs,
+        io::{Read, Seek, SeekFrom},
+        process::{Command, Stdio},
+        thread,
+        time::{Duration, Instant, SystemTime},
+    },
+};
+
+/// Where the lock and log files of the daemons are kept.
+#[cfg(unix)]
+static DAEMON_DIR: &str = "dist/perseus-tailwind";
+
+/// How long to wait for a daemon to write the output before building it directly.
+#[cfg(unix)]
+static BUILD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Runs the CLI in watch mode and stops it once it's no longer the daemon recorded in the lock
+/// file (`$1`), the Perseus CLI (`$2`) exited or the CLI itself stopped.
+#[cfg(unix)]
+static SUPERVISOR: &str = r#"lock=$1; owner=$2; shift 2
+"$@" &
+child=$!
+while sleep 1 && [ "$(head -n 1 "$lock" 2>/dev/null)" = "$$" ] && kill -0 "$owner" 2>/dev/null && kill -0 "$child" 2>/dev/null; do :; done
+kill "$child" 2>/dev/null"#;
+
+/// Builds a bundle with a Tailwind CLI that keeps running in watch mode across engine rebuilds,
+/// starting it if needed. Returns `false` if the bundle has to be built by running the CLI
+/// directly instead.
+#[cfg(unix)]
+pub(crate) fn build(
+    options: &TailwindOptions,
+    bundle: &TailwindBundle,
+    executable: &ResolvedExecutable,
+    in_file: &str,
+    args: &[&str],
+) -> Result<bool, TailwindError> {
+    let Some(owner) = perseus_cli_pid() else {
+        log::debug!("Not running under the Perseus CLI, building without a Tailwind daemon.");
+        return Ok(false);
+    };
+    let lock = options
+        .path(DAEMON_DIR)
+        .join(format!("{}.daemon", bundle.name));
+    let identity = identity(executable, args, owner);
+    let log_path = options
+        .path(DAEMON_DIR)
+        .join(format!("{}.daemon.log", bundle.name));
+    // The daemon reads a generated input that imports the real one, so that rebuilds can be
+    // requested without touching the app's sources, which Perseus watches
+    let input = options
+        .path(DAEMON_DIR)
+        .join(format!("{}.daemon.css", bundle.name));
+
+    match read_lock(&lock) {
+        Some((pid, locked)) if locked == identity && is_alive(pid) => {
+            log::debug!(
+                "Requesting a rebuild of bundle '{}' from daemon {}.",
+                bundle.name,
+                pid
+            );
+            let offset = fs::metadata(&log_path).map_or(0, |metadata| metadata.len());
+            match write_input(&input, options, in_file).map(|_| wait_for_build(&log_path, offset)) {
+                Ok(Outcome::Built) => return Ok(true),
+                Ok(Outcome::Failed) => {
+                    log::debug!(
+                        "Tailwind daemon {} failed to build bundle '{}', running the CLI directly \
+                         to report the error.",
+                        pid,
+                        bundle.name
+                    );
+                    return Ok(false);
+                }
+                Ok(Outcome::TimedOut) | Err(_) => {}
+            }
+            log::warn!(
+                "Tailwind daemon {} didn't rebuild bundle '{}', restarting it.",
+                pid,
+                bundle.name
+            );
+        }
+        Some((pid, _)) if is_alive(pid) => {
+            log::debug!(
+                "Options of bundle '{}' changed, restarting its daemon.",
+                bundle.name
+            )
+        }
+        Some(_) => log::debug!(
+            "Removing the stale daemon lock of bundle '{}'.",
+            bundle.name
+        ),
+        None => {}
+    }
+    // Without the lock, the old daemon stops on its own
+    let _ = fs::remove_file(&lock);
+
+    let io_error = |path: &Path| {
+        let path = path.to_path_buf();
+        move |source| TailwindError::Io { path, source }
+    };
+    let dir = options.path(DAEMON_DIR);
+    fs::create_dir_all(&dir).map_err(io_error(&dir))?;
+    write_input(&input, options, in_file).map_err(io_error(&input))?;
+    let input_arg = input.to_string_lossy();
+    let args: Vec<&str> = args
+        .iter()
+        .scan(false, |after_input, arg| {
+            let arg = if std::mem::replace(after_input, *arg == "-i") {
+                input_arg.as_ref()
+            } else {
+                arg
+            };
+            Some(arg)
+        })
+        .collect();
+    let log_file = fs::File::create(&log_path).map_err(io_error(&log_path))?;
+    let cli = executable.command();
+    let mut command = Command::new("sh");
+    command
+        .args(["-c", SUPERVISOR, "sh"])
+        .arg(&lock)
+        .arg(owner.to_string())
+        .arg(cli.get_program())
+        .args(cli.get_args())
+        .args(&args)
+        .arg("--watch=always")
+        .stdin(Stdio::null())
+        .stdout(log_file.try_clone().map_err(io_error(&log_path))?)
+        .stderr(log_file);
+    if let Some(dir) = cli.get_current_dir() {
+        command.current_dir(dir);
+    }
+    let daemon = command.spawn().map_err(io_error(Path::new("sh")))?;
+    fs::write(&lock, format!("{}\n{}\n", daemon.id(), identity)).map_err(io_error(&lock))?;
+    log::info!(
+        "Started Tailwind daemon {} for bundle '{}', logging to '{}'.",
+        daemon.id(),
+        bundle.name,
+        log_path.display()
+    );
+
+    // The daemon builds the bundle as soon as it starts
+    match wait_for_build(&log_path, 0) {
+        Outcome::Built => Ok(true),
+        Outcome::Failed => Ok(false),
+        Outcome::TimedOut => {
+            log::warn!(
+                "Tailwind daemon for bundle '{}' didn't build it in time, see '{}'.",
+                bundle.name,
+                log_path.display()
+            );
+            Ok(false)
+        }
+    }
+}
+
+/// Writes the daemon's input, which changes on every call so that the daemon rebuilds.
+#[cfg(unix)]
+fn write_input(input: &Path, options: &TailwindOptions, in_file: &str) -> std::io::Result<()> {
+    let requested = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    fs::write(
+        input,
+        format!(
+            "@import {};\n/* rebuild requested at {} */\n",
+            crate::config::js_string(&options.path(in_file).to_string_lossy()),
+            requested
+        ),
+    )
+}
+
+#[cfg(not(unix))]
+pub(crate) fn build(
+    _options: &TailwindOptions,
+    _bundle: &TailwindBundle,
+    _executable: &ResolvedExecutable,
+    _in_file: &str,
+    _args: &[&str],
+) -> Result<bool, TailwindError> {
+    log::debug!("Tailwind daemons are only supported on Unix, running the CLI directly.");
+    Ok(false)
+}
+
+/// Finds the Perseus CLI among the engine's parent processes, which the daemon lives as long as.
+#[cfg(unix)]
+fn perseus_cli_pid() -> Option<u32> {
+    let mut pid = std::process::id();
+    loop {
+        let output = Command::new("ps")
+            .args(["-o", "ppid=,comm=", "-p", &pid.to_string()])
+            .output()
+            .ok()?;
+        let output = String::from_utf8_lossy(&output.stdout);
+        let (parent, comm) = output.trim().split_once(char::is_whitespace)?;
+        if pid != std::process::id() && Path::new(comm.trim()).file_name()? == "perseus" {
+            return Some(pid);
+        }
+        pid = parent.trim().parse().ok()?;
+        if pid <= 1 {
+            return None;
+        }
+    }
+}
+
+/// Identifies the daemon's invocation, so that changed options or a new Perseus CLI restart it.
+#[cfg(unix)]
+fn identity(executable: &ResolvedExecutable, args: &[&str], owner: u32) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{}\n{:?}\n{}", executable.identity(), args, owner));
+    hex(&hasher.finalize())
+}
+
+#[cfg(unix)]
+fn read_lock(lock: &Path) -> Option<(u32, String)> {
+    let contents = fs::read_to_string(lock).ok()?;
+    let mut lines = contents.lines();
+    let pid = lines.next()?.trim().parse().ok()?;
+    Some((pid, lines.next()?.trim().to_string()))
+}
+
+#[cfg(unix)]
+fn is_alive(pid: u32) -> bool {
+    Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// How a build requested from a daemon ended.
+#[cfg(unix)]
+enum Outcome {
+    Built,
+    Failed,
+    TimedOut,
+}
+
+/// Waits for the daemon to report the end of a build in its log, after the first `offset` bytes.
+/// The output file can't tell, since the CLI doesn't write it if the CSS didn't change.
+#[cfg(unix)]
+fn wait_for_build(log_path: &Path, offset: u64) -> Outcome {
+    let deadline = Instant::now() + BUILD_TIMEOUT;
+    while Instant::now() < deadline {
+        let mut log = Vec::new();
+        if let Ok(mut file) = fs::File::open(log_path) {
+            let _ = file
+                .seek(SeekFrom::Start(offset))
+                .and_then(|_| file.read_to_end(&mut log));
+        }
+        let log = String::from_utf8_lossy(&log);
+        // The last line may still be written
+        let complete = log.rsplit_once('\n').map_or("", |(complete, _)| complete);
+        for line in complete.lines() {
+            if line.contains("Done in") {
+                return Outcome::Built;
+            }
+            if !bundle::is_warning(line) && line.to_ascii_lowercase().contains("error") {
+                return Outcome::Failed;
+            }
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    Outcome::TimedOut
+}
@@ -1,8 +1,8 @@
 /** @type {import('tailwindcss').Config} */
 module.exports = {
-    content: ["./src/**/*.rs", "./static/**/*.html"],
+    content: {{content}},{{dark_mode}}
     theme: {
-        extend: {},
+        extend: {{theme_extend}},
     },
-    plugins: [],
+    plugins: [{{plugins}}],
 }
@@ -0,0 +1,114 @@
+use std::{io, path::PathBuf};
+
+/// Errors that can occur while running the Tailwind CLI.
+///
+/// These are returned from the plugin's build and export actions, so Perseus reports them as a
+/// regular build failure instead of aborting the engine with a panic.
+#[derive(Debug, thiserror::Error)]
+pub enum TailwindError {
+    /// The Tailwind CLI executable couldn't be found or started.
+    #[error(
+        "couldn't start the Tailwind CLI `{binary}`, make sure it is installed and on your PATH"
+    )]
+    BinaryNotFound {
+        binary: String,
+        #[source]
+        source: io::Error,
+    },
+    /// The Tailwind CLI exited with a non-zero status for a reason we couldn't classify.
+    #[error("the Tailwind CLI failed ({}):\n{stderr}", exit_code_display(*.code))]
+    NonZeroExit { code: Option<i32>, stderr: String },
+    /// The Tailwind CLI didn't finish within the configured timeout and was killed.
+    #[error("the Tailwind CLI `{binary}` didn't finish within {seconds}s and was stopped, its output so far:\n{stderr}")]
+    Timeout {
+        binary: String,
+        seconds: u64,
+        stderr: String,
+    },
+    /// The input CSS file couldn't be parsed.
+    #[error("syntax error in Tailwind input CSS:\n{message}")]
+    CssSyntax { message: String },
+    /// The selected backend wasn't compiled in.
+    #[error("the `{backend}` backend requires the `{feature}` feature of perseus-tailwind")]
+    BackendUnavailable { backend: String, feature: String },
+    /// The Tailwind configuration file is invalid or couldn't be loaded.
+    #[error("invalid Tailwind configuration:\n{message}")]
+    Config { message: String },
+    /// The standalone Tailwind CLI couldn't be downloaded.
+    #[cfg(engine)]
+    #[error("couldn't download the Tailwind CLI from `{url}`")]
+    Download {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    /// The downloaded Tailwind CLI didn't match the expected checksum.
+    #[error("checksum mismatch for the downloaded Tailwind CLI `{asset}` (expected {expected}, got {actual})")]
+    ChecksumMismatch {
+        asset: String,
+        expected: String,
+        actual: String,
+    },
+    /// No checksum is known for the Tailwind CLI release that should be installed.
+    #[error("no checksum is known for the Tailwind CLI release `{asset}`, set `InstallOptions::sha256` to install it")]
+    MissingChecksum { asset: String },
+    /// There is no standalone Tailwind CLI release for the host platform.
+    #[error("the standalone Tailwind CLI isn't available for {os} on {arch}")]
+    UnsupportedPlatform { os: String, arch: String },
+    /// The version of the Tailwind CLI couldn't be determined from its help output.
+    #[error("couldn't determine the version of the Tailwind CLI from its output:\n{output}")]
+    UnknownVersion { output: String },
+    /// The configured version requirement isn't valid semver.
+    #[error("invalid Tailwind version requirement `{requirement}`: {message}")]
+    InvalidVersionRequirement {
+        requirement: String,
+        message: String,
+    },
+    /// The installed Tailwind CLI doesn't satisfy the configured version requirement.
+    #[error("the Tailwind CLI is version {found}, but version {required} is required")]
+    UnsupportedVersion { found: String, required: String },
+    /// The output file is in a directory watched by Perseus, which would cause build loops.
+    #[error("the Tailwind output `{out_file}` would cause build loops, {suggestion}")]
+    UnsafeOutFile {
+        out_file: String,
+        suggestion: String,
+    },
+    /// The options in a config file or environment variable are invalid.
+    #[error("invalid perseus-tailwind options in {origin}: {message}")]
+    InvalidOptions { origin: String, message: String },
+    /// The Tailwind executable couldn't be parsed, see [`TailwindExecutable`](crate::TailwindExecutable).
+    #[error("invalid Tailwind executable `{value}`, expected a launcher like `npx` or `pnpm dlx` that may be followed by `tailwindcss` or `@tailwindcss/cli` and a version, a path or a comma-separated list of executable names")]
+    InvalidExecutable { value: String },
+    /// Reading or writing a file failed.
+    #[error("I/O error on `{}`", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+}
+
+impl TailwindError {
+    /// Classifies the stderr output of a failed Tailwind run into the most specific error variant.
+    #[cfg(engine)]
+    pub(crate) fn from_failed_run(code: Option<i32>, stderr: &str) -> Self {
+        let message = stderr.trim().to_string();
+        if stderr.contains("CssSyntaxError") {
+            Self::CssSyntax { message }
+        } else if stderr.contains("tailwind.config") {
+            Self::Config { message }
+        } else {
+            Self::NonZeroExit {
+                code,
+                stderr: message,
+            }
+        }
+    }
+}
+
+fn exit_code_display(code: Option<i32>) -> String {
+    match code {
+        Some(code) => format!("exit code {}", code),
+        None => "terminated by signal".to_string(),
+    }
+}
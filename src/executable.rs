@@ -0,0 +1,630 @@
+use crate::TailwindError;
+use serde::{Deserialize, Serialize};
+use std::{fmt, path::PathBuf, str::FromStr};
+#[cfg(engine)]
+use {
+    crate::TailwindMajorVersion,
+    std::{
+        env,
+        io::{self, BufRead, IsTerminal},
+        path::Path,
+        process::{Child, Command, ExitStatus, Output, Stdio},
+        sync::{Arc, Mutex},
+        thread::{self, JoinHandle},
+        time::{Duration, Instant},
+    },
+};
+
+/// The environment variable that overrides [`TailwindOptions::executable`](crate::TailwindOptions).
+///
+/// It accepts the same syntax as [`TailwindExecutable::from_str`], e.g. `npx`,
+/// `pnpm dlx tailwindcss@3.4.17`, `./tools/tailwindcss` or `tailwindcss`.
+pub static EXECUTABLE_ENV_VAR: &str = "PERSEUS_TAILWIND_BIN";
+
+/// How long to wait for the output of the CLI's pipes after it exited, even if the timeout is
+/// over.
+#[cfg(engine)]
+static CLOSE_GRACE: Duration = Duration::from_millis(100);
+
+/// How the Tailwind CLI should be invoked
+///
+/// In config files and environment variables this is written as a string, see
+/// [`TailwindExecutable::from_str`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum TailwindExecutable {
+    /// Use the first of these executable names that can be found on the `PATH`.
+    Search(Vec<String>),
+    /// Use the executable at this path.
+    Path(PathBuf),
+    /// Run Tailwind through a JavaScript package runner.
+    Launcher {
+        /// The package runner
+        launcher: Launcher,
+        /// The package to run, optionally with a version, e.g. `tailwindcss@3.4.17`.\
+        /// Defaults to `tailwindcss`, or `@tailwindcss/cli` if
+        /// [`TailwindMajorVersion::V4`](crate::TailwindMajorVersion) is configured.
+        package: Option<String>,
+    },
+}
+
+impl Default for TailwindExecutable {
+    fn default() -> Self {
+        Self::Search(vec![
+            "tailwindcss".to_string(),
+            "tailwindcli".to_string(),
+            "tailwind".to_string(),
+        ])
+    }
+}
+
+impl FromStr for TailwindExecutable {
+    type Err = TailwindError;
+
+    /// Parses a launcher (`npx`, `pnpm dlx`, `bunx`), optionally followed by the Tailwind package
+    /// and a version (`npx tailwindcss@3.4.17`, `bunx @tailwindcss/cli`), an explicit path
+    /// (anything containing `/` or `\`) or a comma-separated list of names to search on the `PATH`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let words: Vec<&str> = value.split_whitespace().collect();
+        if let Some(launcher) = Launcher::parse(&words) {
+            return Ok(Self::Launcher {
+                launcher,
+                package: None,
+            });
+        }
+        if let [launcher @ .., package] = words.as_slice() {
+            if let (Some(launcher), true) =
+                (Launcher::parse(launcher), is_tailwind_package(package))
+            {
+                return Ok(Self::Launcher {
+                    launcher,
+                    package: Some(package.to_string()),
+                });
+            }
+        }
+        if value.contains(['/', '\\']) {
+            return Ok(Self::Path(value.into()));
+        }
+        let names: Vec<String> = value
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(String::from)
+            .collect();
+        // Executable names don't contain spaces, so this is a launcher that isn't supported
+        if names.iter().any(|name| name.contains(char::is_whitespace)) {
+            return Err(TailwindError::InvalidExecutable {
+                value: value.to_string(),
+            });
+        }
+        Ok(Self::Search(names))
+    }
+}
+
+/// Checks for the `tailwindcss` or `@tailwindcss/cli` package, optionally with a version like
+/// `@3.4.17` or `@latest`.
+fn is_tailwind_package(spec: &str) -> bool {
+    // The `@` of a scope isn't a version separator
+    let (name, version) = match spec.char_indices().skip(1).find(|(_, c)| *c == '@') {
+        Some((at, _)) => (&spec[..at], Some(&spec[at + 1..])),
+        None => (spec, None),
+    };
+    matches!(name, "tailwindcss" | "@tailwindcss/cli") && version != Some("")
+}
+
+impl TryFrom<String> for TailwindExecutable {
+    type Error = TailwindError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<TailwindExecutable> for String {
+    fn from(executable: TailwindExecutable) -> Self {
+        executable.to_string()
+    }
+}
+
+impl fmt::Display for TailwindExecutable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Search(names) => f.write_str(&names.join(",")),
+            Self::Path(path) => write!(f, "{}", path.display()),
+            Self::Launcher {
+                launcher,
+                package: None,
+            } => write!(f, "{}", launcher),
+            Self::Launcher {
+                launcher,
+                package: Some(package),
+            } => write!(f, "{} {}", launcher, package),
+        }
+    }
+}
+
+/// A JavaScript package runner that can download and run the Tailwind CLI on demand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Launcher {
+    /// `npx tailwindcss`
+    Npx,
+    /// `pnpm dlx tailwindcss`
+    PnpmDlx,
+    /// `bunx tailwindcss`
+    Bunx,
+}
+
+impl Launcher {
+    /// Parses the words of a launcher's invocation, e.g. `["pnpm", "dlx"]`.
+    fn parse(words: &[&str]) -> Option<Self> {
+        match words {
+            ["npx"] => Some(Launcher::Npx),
+            ["pnpm", "dlx"] => Some(Launcher::PnpmDlx),
+            ["bunx"] => Some(Launcher::Bunx),
+            _ => None,
+        }
+    }
+
+    /// The launcher executable and the arguments that come before the package name.
+    #[cfg(engine)]
+    fn command(self) -> (&'static str, &'static [&'static str]) {
+        match self {
+            Launcher::Npx => ("npx", &[]),
+            Launcher::PnpmDlx => ("pnpm", &["dlx"]),
+            Launcher::Bunx => ("bunx", &[]),
+        }
+    }
+}
+
+impl fmt::Display for Launcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Launcher::Npx => f.write_str("npx"),
+            Launcher::PnpmDlx => f.write_str("pnpm dlx"),
+            Launcher::Bunx => f.write_str("bunx"),
+        }
+    }
+}
+
+/// A Tailwind CLI invocation resolved to a concrete program.
+#[cfg(engine)]
+#[derive(Debug, Clone)]
+pub(crate) struct ResolvedExecutable {
+    program: PathBuf,
+    args: Vec<String>,
+    working_dir: PathBuf,
+    timeout: Option<Duration>,
+}
+
+#[cfg(engine)]
+impl ResolvedExecutable {
+    /// Resolves the executable, giving the [`EXECUTABLE_ENV_VAR`] environment variable
+    /// precedence over the configured value. Relative paths are resolved against `root`, which is
+    /// also the directory the CLI runs in.
+    pub(crate) fn resolve(
+        configured: &TailwindExecutable,
+        major_version: Option<TailwindMajorVersion>,
+        root: &Path,
+    ) -> Result<Self, TailwindError> {
+        let from_env = env::var(EXECUTABLE_ENV_VAR)
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+            .map(|value| value.parse::<TailwindExecutable>())
+            .transpose()?;
+        let executable = from_env.as_ref().unwrap_or(configured);
+
+        let resolved = match executable {
+            TailwindExecutable::Path(path) => {
+                let path = root.join(path);
+                if !path.is_file() {
+                    return Err(not_found(&path.display().to_string()));
+                }
+                Self::from_path(path, root)
+            }
+            TailwindExecutable::Search(names) => names
+                .iter()
+                .find_map(|name| find_on_path(name))
+                .map(|program| Self::from_path(program, root))
+                .ok_or_else(|| not_found(&names.join(", ")))?,
+            TailwindExecutable::Launcher { launcher, package } => {
+                let (name, args) = launcher.command();
+                let program = find_on_path(name).ok_or_else(|| not_found(name))?;
+                let package = match (package, major_version) {
+                    (Some(package), _) => package.as_str(),
+                    (None, Some(TailwindMajorVersion::V4)) => "@tailwindcss/cli",
+                    (None, _) => "tailwindcss",
+                };
+                Self {
+                    program,
+                    args: args
+                        .iter()
+                        .copied()
+                        .chain([package])
+                        .map(String::from)
+                        .collect(),
+                    working_dir: root.to_path_buf(),
+                    timeout: None,
+                }
+            }
+        };
+
+        match &from_env {
+            Some(_) => log::info!(
+                "Using Tailwind CLI `{}` (from {}).",
+                resolved,
+                EXECUTABLE_ENV_VAR
+            ),
+            None => log::info!("Using Tailwind CLI `{}`.", resolved),
+        }
+        Ok(resolved)
+    }
+
+    /// Uses the executable at a known path, e.g. one installed by the plugin itself.
+    pub(crate) fn from_path(program: PathBuf, root: &Path) -> Self {
+        Self {
+            program,
+            args: Vec::new(),
+            working_dir: root.to_path_buf(),
+            timeout: None,
+        }
+    }
+
+    /// Stops runs of the CLI that take longer than `timeout`.
+    pub(crate) fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Identifies the CLI binary by its invocation, size and modification time, so that changes
+    /// to the installed CLI can be detected without running it.
+    pub(crate) fn identity(&self) -> String {
+        let metadata = self
+            .program
+            .metadata()
+            .map(|metadata| format!("{} {:?}", metadata.len(), metadata.modified().ok()))
+            .unwrap_or_default();
+        format!("{} {}", self, metadata)
+    }
+
+    /// Creates a command that runs the Tailwind CLI, ready to receive its arguments.
+    pub(crate) fn command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command.args(&self.args).current_dir(&self.working_dir);
+        command
+    }
+
+    /// Runs the Tailwind CLI with the given arguments and waits for it to finish. If it doesn't
+    /// finish within the timeout, it is killed along with the processes it started.
+    pub(crate) fn output(&self, args: &[&str]) -> Result<Output, TailwindError> {
+        self.stream(args, |_| {})
+    }
+
+    /// Like [`ResolvedExecutable::output`], but also passes every line the CLI writes to stdout
+    /// or stderr to `on_line` as soon as it's written.
+    pub(crate) fn stream(
+        &self,
+        args: &[&str],
+        on_line: impl Fn(&str) + Send + Sync + 'static,
+    ) -> Result<Output, TailwindError> {
+        let on_line: Arc<dyn Fn(&str) + Send + Sync> = Arc::new(on_line);
+        let mut command = self.command();
+        command
+            .args(args)
+            // Launchers like npx may wait for input otherwise
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        // A process group of its own lets a timeout also stop the CLI that a launcher started, but
+        // in a terminal it would no longer get the Ctrl-C meant for the whole build
+        let own_group = cfg!(unix) && !io::stdin().is_terminal();
+        #[cfg(unix)]
+        if own_group {
+            std::os::unix::process::CommandExt::process_group(&mut command, 0);
+        }
+        let mut child = command.spawn().map_err(|source| self.error(source))?;
+        let stdout = Pipe::read(child.stdout.take(), on_line.clone());
+        let stderr = Pipe::read(child.stderr.take(), on_line);
+
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        let exited = wait_for_exit(&mut child, deadline).map_err(|source| self.error(source))?;
+        // Processes the CLI left behind would keep the pipes open
+        let status = kill(&mut child, own_group).map_err(|source| self.error(source))?;
+        if !exited {
+            return Err(TailwindError::Timeout {
+                binary: self.to_string(),
+                seconds: self.timeout.unwrap_or_default().as_secs(),
+                stderr: String::from_utf8_lossy(&stderr.snapshot())
+                    .trim()
+                    .to_string(),
+            });
+        }
+        let deadline = deadline.map(|deadline| deadline.max(Instant::now() + CLOSE_GRACE));
+        Ok(Output {
+            status,
+            stdout: stdout.finish(deadline),
+            stderr: stderr.finish(deadline),
+        })
+    }
+
+    fn error(&self, source: io::Error) -> TailwindError {
+        match source.kind() {
+            io::ErrorKind::NotFound => TailwindError::BinaryNotFound {
+                binary: self.to_string(),
+                source,
+            },
+            _ => TailwindError::Io {
+                path: self.program.clone(),
+                source,
+            },
+        }
+    }
+}
+
+/// Collects the output of a child process on a separate thread, so it can be read after a
+/// timeout without waiting for the pipe to close.
+#[cfg(engine)]
+struct Pipe {
+    buffer: Arc<Mutex<Vec<u8>>>,
+    reader: Option<JoinHandle<()>>,
+}
+
+#[cfg(engine)]
+impl Pipe {
+    fn read(
+        pipe: Option<impl io::Read + Send + 'static>,
+        on_line: Arc<dyn Fn(&str) + Send + Sync>,
+    ) -> Self {
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let reader = pipe.map(|pipe| {
+            let buffer = buffer.clone();
+            thread::spawn(move || {
+                let mut pipe = io::BufReader::new(pipe);
+                let mut line = Vec::new();
+                while let Ok(1..) = pipe.read_until(b'\n', &mut line) {
+                    on_line(String::from_utf8_lossy(&line).trim_end());
+                    buffer
+                        .lock()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner())
+                        .append(&mut line);
+                }
+            })
+        });
+        Self { buffer, reader }
+    }
+
+    /// Everything read so far.
+    fn snapshot(&self) -> Vec<u8> {
+        self.buffer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Waits for the pipe to close, but not past the deadline, and returns everything read from
+    /// it.
+    fn finish(mut self, deadline: Option<Instant>) -> Vec<u8> {
+        if let Some(reader) = self.reader.take() {
+            if let Some(deadline) = deadline {
+                while !reader.is_finished() && Instant::now() < deadline {
+                    thread::sleep(Duration::from_millis(10));
+                }
+            }
+            if deadline.is_none() || reader.is_finished() {
+                let _ = reader.join();
+            }
+        }
+        self.snapshot()
+    }
+}
+
+/// Waits for the child to exit, returning `false` if it's still running at the deadline.
+///
+/// On Unix the child isn't reaped, so its process ID, which is also the ID of its process group,
+/// can't be reused before [`kill`] stops the group.
+#[cfg(all(engine, unix))]
+fn wait_for_exit(child: &mut Child, deadline: Option<Instant>) -> io::Result<bool> {
+    let flags = libc::WEXITED | libc::WNOWAIT | deadline.map_or(0, |_| libc::WNOHANG);
+    loop {
+        // SAFETY: an all-zero `siginfo_t` is valid, and `waitid` only writes to it
+        let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+        if unsafe { libc::waitid(libc::P_PID, child.id(), &mut info, flags) } == -1 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(err);
+        }
+        // SAFETY: `waitid` succeeded, so `info` is zeroed or describes the exited child
+        if unsafe { info.si_pid() } != 0 {
+            return Ok(true);
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Ok(false);
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+/// Waits for the child to exit, returning `false` if it's still running at the deadline.
+#[cfg(all(engine, not(unix)))]
+fn wait_for_exit(child: &mut Child, deadline: Option<Instant>) -> io::Result<bool> {
+    let Some(deadline) = deadline else {
+        return child.wait().map(|_| true);
+    };
+    loop {
+        if child.try_wait()?.is_some() {
+            return Ok(true);
+        }
+        if Instant::now() >= deadline {
+            return Ok(false);
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+/// Kills the child, and everything in its process group if it leads one of its own, if they're
+/// still running, and reaps the child.
+#[cfg(engine)]
+fn kill(child: &mut Child, own_group: bool) -> io::Result<ExitStatus> {
+    #[cfg(unix)]
+    if own_group {
+        // SAFETY: the child isn't reaped yet, so the group is still the one it leads
+        unsafe { libc::killpg(child.id() as libc::pid_t, libc::SIGKILL) };
+    }
+    #[cfg(not(unix))]
+    let _ = own_group;
+    let _ = child.kill();
+    child.wait()
+}
+
+#[cfg(engine)]
+impl fmt::Display for ResolvedExecutable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.program.display())?;
+        for arg in &self.args {
+            write!(f, " {}", arg)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(engine)]
+fn not_found(binary: &str) -> TailwindError {
+    TailwindError::BinaryNotFound {
+        binary: binary.to_string(),
+        source: io::Error::new(io::ErrorKind::NotFound, "executable not found"),
+    }
+}
+
+/// Looks up an executable name on the `PATH`, also trying the usual extensions on Windows.
+#[cfg(engine)]
+fn find_on_path(name: &str) -> Option<PathBuf> {
+    let extensions: &[&str] = if cfg!(windows) {
+        &["exe", "cmd", "bat"]
+    } else {
+        &[]
+    };
+    let path = env::var_os("PATH").unwrap_or_default();
+    env::split_paths(&path).find_map(|dir| {
+        let candidate = dir.join(name);
+        if is_executable(&candidate) {
+            return Some(candidate);
+        }
+        extensions
+            .iter()
+            .map(|extension| candidate.with_extension(extension))
+            .find(|candidate| is_executable(candidate))
+    })
+}
+
+#[cfg(engine)]
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata()
+            .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+#[cfg(all(test, engine))]
+mod tests {
+    use super::*;
+
+    fn launcher(launcher: Launcher, package: Option<&str>) -> TailwindExecutable {
+        TailwindExecutable::Launcher {
+            launcher,
+            package: package.map(String::from),
+        }
+    }
+
+    fn search(names: &[&str]) -> TailwindExecutable {
+        TailwindExecutable::Search(names.iter().map(|name| name.to_string()).collect())
+    }
+
+    #[test]
+    fn parses_executables() {
+        let cases = [
+            ("npx", launcher(Launcher::Npx, None)),
+            (" bunx ", launcher(Launcher::Bunx, None)),
+            ("pnpm dlx", launcher(Launcher::PnpmDlx, None)),
+            (
+                "npx tailwindcss",
+                launcher(Launcher::Npx, Some("tailwindcss")),
+            ),
+            (
+                "npx tailwindcss@3.4.1",
+                launcher(Launcher::Npx, Some("tailwindcss@3.4.1")),
+            ),
+            (
+                "pnpm  dlx tailwindcss@latest",
+                launcher(Launcher::PnpmDlx, Some("tailwindcss@latest")),
+            ),
+            (
+                "bunx @tailwindcss/cli",
+                launcher(Launcher::Bunx, Some("@tailwindcss/cli")),
+            ),
+            (
+                "npx @tailwindcss/cli@4.0.0",
+                launcher(Launcher::Npx, Some("@tailwindcss/cli@4.0.0")),
+            ),
+            (
+                "./tools/tailwindcss",
+                TailwindExecutable::Path("./tools/tailwindcss".into()),
+            ),
+            (
+                "/opt/my tools/tailwindcss",
+                TailwindExecutable::Path("/opt/my tools/tailwindcss".into()),
+            ),
+            (
+                r"C:\Program Files\tailwindcss.exe",
+                TailwindExecutable::Path(r"C:\Program Files\tailwindcss.exe".into()),
+            ),
+            ("tailwindcss", search(&["tailwindcss"])),
+            ("pnpm", search(&["pnpm"])),
+            (
+                "tailwindcss, tailwindcli,,tailwind",
+                search(&["tailwindcss", "tailwindcli", "tailwind"]),
+            ),
+        ];
+        for (value, expected) in cases {
+            let executable: TailwindExecutable = value.parse().unwrap();
+            assert_eq!(executable, expected, "{}", value);
+            assert_eq!(
+                executable
+                    .to_string()
+                    .parse::<TailwindExecutable>()
+                    .unwrap(),
+                expected,
+                "{}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unsupported_launchers() {
+        for value in [
+            "pnpm tailwindcss",
+            "npx postcss",
+            "npx tailwindcss@",
+            "npx --yes tailwindcss",
+            "yarn dlx tailwindcss",
+        ] {
+            assert!(
+                matches!(
+                    value.parse::<TailwindExecutable>(),
+                    Err(TailwindError::InvalidExecutable { .. })
+                ),
+                "{}",
+                value
+            );
+        }
+    }
+}
@@ -0,0 +1,325 @@
+#![cfg(engine)]
+
+use crate::{
+    config::{self, js_string},
+    write_if_changed, TailwindBundle, TailwindError, TailwindMajorVersion, TailwindOptions,
+};
+use proc_macro2::{TokenStream, TokenTree};
+use std::{collections::BTreeSet, fs, path::PathBuf};
+use syn::visit::Visit;
+
+/// The directory generated class lists and the wrappers that feed them to Tailwind are written
+/// to.
+static EXTRACT_DIR: &str = "dist/perseus-tailwind";
+
+/// The inputs to use instead of the bundle's own so that Tailwind also sees the extracted classes.
+pub(crate) struct ExtractedInputs {
+    /// The input CSS file, which imports the bundle's one for Tailwind v4
+    pub(crate) in_file: String,
+    /// The config file, which extends the bundle's one for Tailwind v3
+    pub(crate) config: Option<String>,
+    /// The list of extracted classes, to be added to explicit content globs
+    pub(crate) classes_file: String,
+}
+
+/// Extracts the class names used in `view!` and `tw!` invocations in the bundle's Rust sources, writes
+/// them to a file and generates the wrappers that make Tailwind scan that file in addition to the
+/// bundle's normal content.
+pub(crate) fn prepare(
+    bundle: &TailwindBundle,
+    options: &TailwindOptions,
+    major_version: TailwindMajorVersion,
+) -> Result<ExtractedInputs, TailwindError> {
+    let files = content_files(bundle, options, "rs")?;
+    let classes = extract_classes(&files);
+    log::debug!(
+        "Extracted {} class names from {} Rust files for bundle '{}'.",
+        classes.len(),
+        files.len(),
+        bundle.name
+    );
+
+    let dir = options.path(EXTRACT_DIR);
+    let classes_file = dir.join(format!("{}.classes.txt", bundle.name));
+    write_if_changed(
+        &classes_file,
+        &classes.into_iter().collect::<Vec<_>>().join("\n"),
+    )?;
+
+    let mut inputs = ExtractedInputs {
+        in_file: bundle.in_file.clone(),
+        config: bundle.config.clone(),
+        classes_file: classes_file.display().to_string(),
+    };
+    match major_version {
+        // Explicit content globs replace the config's, so the class list is just added to them
+        TailwindMajorVersion::V3 if !bundle.content.is_empty() => {}
+        TailwindMajorVersion::V3 => {
+            let base = match (&bundle.config, &options.config) {
+                (Some(config), _) => options.path(config),
+                (None, Some(_)) => options.path(config::GENERATED_CONFIG),
+                (None, None) => options.path("tailwind.config.js"),
+            };
+            let wrapper = dir.join(format!("{}.config.cjs", bundle.name));
+            write_if_changed(
+                &wrapper,
+                &format!(
+                    "// Generated by perseus-tailwind, don't edit.\n\
+                     const config = require({base});\n\
+                     const content = Array.isArray(config.content) ? {{ files: config.content }} \
+                     : config.content || {{ files: [] }};\n\
+                     module.exports = {{ ...config, content: {{ ...content, files: \
+                     [...content.files, {classes}] }} }};\n",
+                    base = js_string(&base.display().to_string()),
+                    classes = js_string(&inputs.classes_file),
+                ),
+            )?;
+            inputs.config = Some(wrapper.display().to_string());
+        }
+        TailwindMajorVersion::V4 => {
+            let wrapper = dir.join(format!("{}.css", bundle.name));
+            write_if_changed(
+                &wrapper,
+                &format!(
+                    "/* Generated by perseus-tailwind, don't edit. */\n@import {};\n@source {};\n",
+                    js_string(&options.path(&bundle.in_file).display().to_string()),
+                    js_string(&format!("./{}.classes.txt", bundle.name)),
+                ),
+            )?;
+            inputs.in_file = wrapper.display().to_string();
+        }
+    }
+    Ok(inputs)
+}
+
+/// Collects the class names a bundle uses, from `view!` and `tw!` invocations in its Rust sources
+/// and from `class` attributes in its HTML files.
+#[cfg_attr(not(feature = "railwind"), allow(dead_code))]
+pub(crate) fn bundle_classes(
+    bundle: &TailwindBundle,
+    options: &TailwindOptions,
+) -> Result<BTreeSet<String>, TailwindError> {
+    let mut classes = extract_classes(&content_files(bundle, options, "rs")?);
+    for file in content_files(bundle, options, "html")? {
+        match fs::read_to_string(&file) {
+            Ok(html) => scan_html(&html, &mut classes),
+            Err(err) => log::debug!("Couldn't read '{}': {}", file.display(), err),
+        }
+    }
+    Ok(classes)
+}
+
+/// Finds the values of `class="..."` attributes in HTML.
+fn scan_html(html: &str, classes: &mut BTreeSet<String>) {
+    for quote in ['"', '\''] {
+        let attribute = format!("class={}", quote);
+        for (index, _) in html.match_indices(&attribute) {
+            let value = &html[index + attribute.len()..];
+            if let Some(end) = value.find(quote) {
+                add_candidates(&value[..end], classes);
+            }
+        }
+    }
+}
+
+/// Extracts the candidate class names from the `view!` and `tw!` invocations in the given Rust
+/// files.
+fn extract_classes(files: &[PathBuf]) -> BTreeSet<String> {
+    let mut visitor = ViewVisitor::default();
+    for file in files {
+        let source = match fs::read_to_string(file) {
+            Ok(source) => source,
+            Err(err) => {
+                log::debug!("Couldn't read '{}': {}", file.display(), err);
+                continue;
+            }
+        };
+        match syn::parse_file(&source) {
+            Ok(file) => visitor.visit_file(&file),
+            Err(err) => log::debug!("Couldn't parse '{}': {}", file.display(), err),
+        }
+    }
+    visitor.classes
+}
+
+#[derive(Default)]
+struct ViewVisitor {
+    classes: BTreeSet<String>,
+}
+
+impl<'ast> Visit<'ast> for ViewVisitor {
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        let name = mac
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string());
+        match name.as_deref() {
+            Some("view") => scan_view(mac.tokens.clone(), &mut self.classes),
+            // `tw!` class strings can be used anywhere, not only in `view!`s
+            Some("tw") => {
+                for token in mac.tokens.clone() {
+                    collect_literals(&token, &mut self.classes);
+                }
+            }
+            _ => {}
+        }
+        syn::visit::visit_macro(self, mac);
+    }
+}
+
+/// Finds `class=<expr>` and `class:<name>=<expr>` attributes in the tokens of a `view!`, including
+/// nested elements and `view!`s.
+fn scan_view(tokens: TokenStream, classes: &mut BTreeSet<String>) {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    let mut i = 0;
+    while i < tokens.len() {
+        match &tokens[i] {
+            TokenTree::Ident(ident) if ident == "class" => {
+                let mut j = i + 1;
+                if is_punct(tokens.get(j), ':') {
+                    // `class:name=<bool>` toggles a single class
+                    let mut name = String::new();
+                    j += 1;
+                    while j < tokens.len()
+                        && !is_punct(tokens.get(j), '=')
+                        && !is_punct(tokens.get(j), ',')
+                    {
+                        name.push_str(&tokens[j].to_string());
+                        j += 1;
+                    }
+                    add_candidates(&name, classes);
+                }
+                if is_punct(tokens.get(j), '=') {
+                    // The attribute's expression runs until the next comma at this level
+                    j += 1;
+                    while j < tokens.len() && !is_punct(tokens.get(j), ',') {
+                        collect_literals(&tokens[j], classes);
+                        j += 1;
+                    }
+                }
+                i = j;
+                continue;
+            }
+            TokenTree::Group(group) => scan_view(group.stream(), classes),
+            _ => {}
+        }
+        i += 1;
+    }
+}
+
+/// Collects the string literals in a class expression, e.g. in `format!` arguments or the
+/// branches of an `if`.
+fn collect_literals(token: &TokenTree, classes: &mut BTreeSet<String>) {
+    match token {
+        TokenTree::Literal(literal) => {
+            if let Ok(literal) = syn::parse2::<syn::LitStr>(token.clone().into()) {
+                add_candidates(&literal.value(), classes);
+            } else {
+                log::trace!("Ignoring non-string literal {}", literal);
+            }
+        }
+        TokenTree::Group(group) => {
+            for token in group.stream() {
+                collect_literals(&token, classes);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn add_candidates(value: &str, classes: &mut BTreeSet<String>) {
+    classes.extend(
+        value
+            .split_whitespace()
+            // Skip `format!` placeholders, the classes they produce can't be known statically
+            .filter(|class| !class.contains(['{', '}']))
+            .map(String::from),
+    );
+}
+
+fn is_punct(token: Option<&TokenTree>, c: char) -> bool {
+    matches!(token, Some(TokenTree::Punct(punct)) if punct.as_char() == c)
+}
+
+/// Lists the files with the given extension in the directories a bundle scans.
+fn content_files(
+    bundle: &TailwindBundle,
+    options: &TailwindOptions,
+    extension: &str,
+) -> Result<Vec<PathBuf>, TailwindError> {
+    let mut files = bundle.content_files(options)?;
+    files.retain(|file| file.extension().is_some_and(|ext| ext == extension));
+    Ok(files)
+}
+
+#[cfg(all(test, engine))]
+mod tests {
+    use super::*;
+
+    fn classes(source: &str) -> Vec<String> {
+        let mut visitor = ViewVisitor::default();
+        visitor.visit_file(&syn::parse_file(source).unwrap());
+        visitor.classes.into_iter().collect()
+    }
+
+    #[test]
+    fn collects_format_arguments() {
+        let source = r#"
+            fn page() {
+                view! { div(class = format!("px-4 {} {}", color, "rounded-lg")) { "Hi" } }
+            }
+        "#;
+        assert_eq!(classes(source), ["px-4", "rounded-lg"]);
+    }
+
+    #[test]
+    fn collects_if_branches() {
+        let source = r#"
+            fn page() {
+                view! { div(class = if active { "font-bold" } else { "font-normal" }, id = "a") }
+            }
+        "#;
+        assert_eq!(classes(source), ["font-bold", "font-normal"]);
+    }
+
+    #[test]
+    fn collects_toggled_classes() {
+        let source = r#"
+            fn page() {
+                view! { div(class:hidden = collapsed, class:bg-red-500 = failed) }
+            }
+        "#;
+        assert_eq!(classes(source), ["bg-red-500", "hidden"]);
+    }
+
+    #[test]
+    fn collects_classes_split_across_lines() {
+        let source = r#"
+            fn page() {
+                view! {
+                    div(class = "flex
+                                 items-center") {
+                        span(class = "text-sm")
+                    }
+                }
+            }
+        "#;
+        assert_eq!(classes(source), ["flex", "items-center", "text-sm"]);
+    }
+
+    #[test]
+    fn ignores_comments() {
+        let source = r#"
+            fn page() {
+                // view! { div(class = "line-comment") }
+                view! {
+                    /* div(class = "block-comment") */
+                    div(class = "p-2") // "trailing-comment"
+                }
+                let extra = tw!("m-1" /* "tw-comment" */);
+            }
+        "#;
+        assert_eq!(classes(source), ["m-1", "p-2"]);
+    }
+}
@@ -0,0 +1,102 @@
+#![cfg(engine)]
+
+use crate::{
+    backend::TailwindBackend, hex, TailwindBundle, TailwindError, TailwindMode, TailwindOptions,
+};
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// The config files that affect Tailwind's output if they exist.
+static CONFIG_FILES: &[&str] = &[
+    "tailwind.config.js",
+    "tailwind.config.cjs",
+    "tailwind.config.mjs",
+    "tailwind.config.ts",
+    "postcss.config.js",
+    "postcss.config.cjs",
+];
+
+/// Computes a fingerprint of everything that affects the CSS Tailwind generates for a bundle: the
+/// options, the backend, the input file, the config and the contents of the scanned
+/// directories.
+///
+/// The CLI is identified by its path, size and modification time rather than by asking it for its
+/// version, so that an up-to-date build doesn't have to start any process at all.
+pub(crate) fn compute(
+    options: &TailwindOptions,
+    bundle: &TailwindBundle,
+    mode: TailwindMode,
+    backend: &dyn TailwindBackend,
+) -> Result<String, TailwindError> {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{:?}\n{:?}\n{:?}\n", options, bundle, mode));
+    hasher.update(backend.identity());
+
+    for file in inputs(options, bundle)? {
+        hasher.update(file.to_string_lossy().as_bytes());
+        match fs::read(&file) {
+            Ok(contents) => {
+                hasher.update(contents.len().to_le_bytes());
+                hasher.update(contents);
+            }
+            Err(_) => hasher.update(b"<missing>"),
+        }
+    }
+
+    Ok(hex(&hasher.finalize()))
+}
+
+/// The modification times of the files a bundle is built from, which are cheaper to compare than
+/// a fingerprint when checking for changes repeatedly.
+pub(crate) fn modification_times(
+    options: &TailwindOptions,
+    bundle: &TailwindBundle,
+) -> Result<Vec<(PathBuf, Option<SystemTime>)>, TailwindError> {
+    Ok(inputs(options, bundle)?
+        .into_iter()
+        .map(|file| {
+            let modified = fs::metadata(&file)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            (file, modified)
+        })
+        .collect())
+}
+
+/// The input file, the config files and the scanned files of a bundle, which may not all exist.
+fn inputs(
+    options: &TailwindOptions,
+    bundle: &TailwindBundle,
+) -> Result<Vec<PathBuf>, TailwindError> {
+    let out_file = options.path(&bundle.out_file);
+    let mut files = vec![options.path(&bundle.in_file)];
+    files.extend(bundle.config.iter().map(|config| options.path(config)));
+    files.extend(CONFIG_FILES.iter().map(|config| options.path(config)));
+    files.extend(bundle.content_files(options)?);
+    files.retain(|file| *file != out_file && *file != fingerprint_path(&out_file));
+    Ok(files)
+}
+
+/// Checks whether the output exists and was built from inputs with the given fingerprint.
+pub(crate) fn is_fresh(out_file: &Path, fingerprint: &str) -> bool {
+    out_file.is_file()
+        && fs::read_to_string(fingerprint_path(out_file))
+            .map(|stored| stored.trim() == fingerprint)
+            .unwrap_or(false)
+}
+
+/// Stores the fingerprint next to the output file.
+pub(crate) fn store(out_file: &Path, fingerprint: &str) -> Result<(), TailwindError> {
+    let path = fingerprint_path(out_file);
+    fs::write(&path, fingerprint).map_err(|source| TailwindError::Io { path, source })
+}
+
+fn fingerprint_path(out_file: &Path) -> PathBuf {
+    let mut path = out_file.as_os_str().to_owned();
+    path.push(".fingerprint");
+    PathBuf::from(path)
+}
@@ -1,20 +1,21 @@
 //! This is a simple plugin for Perseus that runs the Tailwind CLI at build time.
-//! 
+//!
 //! Starting point derived from https://github.com/wingertge/perseus-tailwind
-//! * Removed automatic installation of TailwindCli so this must exsist on the build env
+//! * Automatic installation of the standalone Tailwind CLI is opt-in through [`InstallOptions`]
 //! * Updated to work with the latest Perseus 0.4.0-beta.14
-//! 
+//!
 //! It will look for class names in Rust files in `src` and HTML files in `static`.
-//! Further configuration can be done as usual in `tailwind.config.js`.
+//! Further configuration can be done as usual in `tailwind.config.js` for Tailwind v3, or in the
+//! input CSS file for Tailwind v4.
 //!
 //! # Usage
 //!
 //! Add the plugin to you Perseus App in your Perseus main function.
 //!
 //! ```
-//! # use perseus::PerseusApp;
+//! # use perseus::prelude::*;
 //! # use perseus::plugins::Plugins;
-//! PerseusApp::new()
+//! PerseusApp::<PerseusNodeType>::new()
 //!     .plugins(Plugins::new().plugin(
 //!         perseus_tailwind::get_tailwind_plugin,
 //!         perseus_tailwind::TailwindOptions {
@@ -22,6 +23,7 @@
 //!             // Don't put this in /static, it will trigger build loops.
 //!             // Put this in /dist and use a static alias instead.
 //!             out_file: "dist/static/tailwind.css".into(),
+//!             ..Default::default()
 //!         },
 //!     ))
 //!     .static_alias("/static/tailwind.css", "dist/static/tailwind.css")
@@ -30,27 +32,321 @@
 //!
 //! If you're already using plugins just add the plugin to your `Plugins` as usual.
 //!
+//! Alternatively, set `serve_at` and the plugin takes care of both the static alias and the
+//! stylesheet `<link>`:
+//!
+//! ```
+//! # use perseus::prelude::*;
+//! # use perseus::plugins::Plugins;
+//! PerseusApp::<PerseusNodeType>::new()
+//!     .plugins(Plugins::new().plugin(
+//!         perseus_tailwind::get_tailwind_plugin,
+//!         perseus_tailwind::TailwindOptions {
+//!             serve_at: Some("/tailwind".into()),
+//!             ..Default::default()
+//!         },
+//!     ))
+//! # ;
+//! ```
+//!
+//! With the `macros` feature, class strings can be written as `tw!("px-4 py-2")` to have every
+//! class checked against Tailwind and the project's theme at compile time.
+//!
+//! With the `railwind` feature, [`TailwindBackendKind::Railwind`] generates the CSS inside the
+//! engine, for build machines that can't run the Tailwind CLI.
+//!
 //! # Stability
 //!
 //! The plugin is fairly simple and shouldn't break anything since it just executes the Tailwind CLI.
 
+mod backend;
+mod background;
+mod bundle;
+mod config;
+mod critical;
+mod daemon;
+mod error;
+mod executable;
+mod extract;
+mod fingerprint;
+mod install;
+mod manifest;
+mod mode;
+mod overrides;
+mod paths;
+mod railwind;
+mod serve;
+mod version;
+mod watch;
+
 #[cfg(engine)]
-use perseus::plugins::PluginAction;
+use backend::TailwindBackend;
+pub use backend::TailwindBackendKind;
+pub use bundle::TailwindBundle;
+pub use config::{ConfigTemplate, DarkMode, TailwindConfig, DEFAULT_CONTENT};
+pub use error::TailwindError;
+pub use executable::{Launcher, TailwindExecutable, EXECUTABLE_ENV_VAR};
+pub use install::{InstallOptions, DEFAULT_BASE_URL, PINNED_VERSION};
+pub use manifest::{stylesheet_url, MANIFEST_FILE};
+pub use mode::TailwindMode;
+pub use overrides::{ENV_PREFIX, OPTIONS_FILE};
 use perseus::plugins::{empty_control_actions_registrar, Plugin, PluginEnv};
 #[cfg(engine)]
-use std::{fs::File, io::Write, path::PathBuf, process::Command};
+use perseus::{engine::EngineOperation, plugins::PluginAction};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+#[cfg(engine)]
+use std::{fs::File, io::Write, path::Path};
+pub use version::TailwindMajorVersion;
+
+#[cfg(feature = "macros")]
+pub use perseus_tailwind_macros::tw;
 
 static PLUGIN_NAME: &str = "tailwind-plugin";
 
+/// Locked while Tailwind runs, so builds from the plugin's actions, the background thread, the
+/// watcher and other engine processes, like the builds of `perseus serve -w`, never write the same
+/// outputs at once.
+#[cfg(engine)]
+static BUILD_LOCK: &str = "dist/perseus-tailwind/build.lock";
+
 /// Options for the Tailwind CLI
-#[derive(Debug)]
+///
+/// The values set in code are defaults that can be overridden without recompiling the engine,
+/// from lowest to highest precedence, by:
+/// * the `[package.metadata.perseus-tailwind]` table in `Cargo.toml`
+/// * a `perseus-tailwind.toml` file next to `Cargo.toml`
+/// * `PERSEUS_TAILWIND_<FIELD>` environment variables, e.g. `PERSEUS_TAILWIND_OUT_FILE`, whose
+///   values are parsed as TOML values or used as plain strings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct TailwindOptions {
-    /// The path to the input CSS file
+    /// The path to the input CSS file.\
+    /// Ignored if `bundles` isn't empty.
     pub in_file: String,
     /// The path to the CSS file output by the CLI.\
     /// **DO NOT PUT THIS IN `/static` UNLESS YOU LIKE BUILD LOOPS!**\
     /// Always put it somewhere in `/dist` use static aliases instead.\
+    /// Ignored if `bundles` isn't empty.\
+    /// The build fails if this is anywhere Perseus watches for changes, see
+    /// `redirect_unsafe_out_file`.
     pub out_file: String,
+    /// Several bundles to build in parallel instead of the single `in_file`/`out_file` pair.\
+    /// Empty by default.
+    pub bundles: Vec<TailwindBundle>,
+    /// What generates the CSS.\
+    /// Defaults to [`TailwindBackendKind::Cli`].
+    pub backend: TailwindBackendKind,
+    /// How to invoke the Tailwind CLI.\
+    /// Defaults to searching the `PATH` for `tailwindcss`, `tailwindcli` and `tailwind`.
+    /// Can be overridden with the `PERSEUS_TAILWIND_BIN` environment variable.
+    pub executable: TailwindExecutable,
+    /// Download the standalone Tailwind CLI if `executable` can't be found.\
+    /// Disabled by default.
+    pub install: Option<InstallOptions>,
+    /// A semver requirement the Tailwind CLI's version has to satisfy, e.g. `"~3.4"`.\
+    /// The build fails if the installed CLI doesn't match it.
+    pub version: Option<String>,
+    /// The major version of Tailwind in use, which decides how it is configured and invoked.\
+    /// Detected from the CLI if not set.
+    pub major_version: Option<TailwindMajorVersion>,
+    /// Whether to build development or production CSS.\
+    /// Defaults to [`TailwindMode::Auto`].
+    pub mode: TailwindMode,
+    /// Minify the output. Defaults to minifying in production mode only.
+    pub minify: Option<bool>,
+    /// Run the output through PostCSS using the project's PostCSS config. Only supported by
+    /// Tailwind v3, v4 handles vendor prefixes and nesting on its own.
+    pub postcss: bool,
+    /// Skip running Tailwind when neither the options, the CLI, the input file, the config nor
+    /// any file in `src` or `static` changed since the last build. Enabled by default.
+    pub incremental: bool,
+    /// Write output files that would cause build loops into `dist` instead of failing the
+    /// build, e.g. `static/tailwind.css` becomes `dist/static/tailwind.css`. A matching static
+    /// alias is suggested in the logs. Disabled by default.
+    pub redirect_unsafe_out_file: bool,
+    /// Also write every output file under a name containing a hash of its contents, e.g.
+    /// `tailwind.1a2b3c4d.css`, so it can be served with long cache lifetimes. Older hashed files
+    /// are removed and the current names are recorded in a [`MANIFEST_FILE`] next to the outputs,
+    /// see [`stylesheet_url`]. Can't be combined with `watch`. Disabled by default.
+    pub hashed_filenames: bool,
+    /// The URL the output files are served at, e.g. `"/tailwind"` for
+    /// `/tailwind/tailwind.css`. When set, the plugin registers the static alias for them and
+    /// adds a stylesheet `<link>` for every bundle to the head of all pages, so the app needs
+    /// neither its own `.static_alias()` nor a `<link>` in its index view. Not set by default.
+    pub serve_at: Option<String>,
+    /// After `perseus export`, inline the rules of the bundles' stylesheets that match elements of
+    /// each exported page in a `<style>` tag and load the full stylesheet without blocking
+    /// rendering. Applies to the stylesheet `<link>`s of the bundles' output files, whether added
+    /// through `serve_at` or by the app. Disabled by default.
+    pub inline_critical_css: bool,
+    /// Rebuild the CSS while the server runs whenever the input file, the config or a file in the
+    /// scanned directories is modified, and tell the live reload server of `perseus serve -w` to
+    /// reload the page. CSS changes then don't have to wait for the engine to be rebuilt, but the
+    /// whole page is still reloaded since Perseus' live reloading doesn't support swapping only
+    /// the stylesheets. Meant for development, e.g. `watch: cfg!(debug_assertions)`, and can't be
+    /// combined with `hashed_filenames`. Disabled by default.
+    pub watch: bool,
+    /// Keep the Tailwind CLI running in watch mode for as long as the Perseus CLI runs, instead of
+    /// starting it for every build, which saves its startup time on each reload of
+    /// `perseus serve -w`. Builds ask the running CLI for a rebuild and wait for its output. The
+    /// daemon is tracked by a lock file in `dist/perseus-tailwind` and stops when the Perseus CLI
+    /// exits or another daemon replaces it. Only applies to the Tailwind CLI on Unix. Disabled by
+    /// default.
+    pub daemon: bool,
+    /// Build the CSS on a background thread while Perseus builds the pages, instead of before.
+    /// The build waits for it to finish before the HTML shell is created and fails if the CSS
+    /// couldn't be built. Disabled by default.
+    pub background: bool,
+    /// How many seconds a run of the Tailwind CLI may take before it is killed, along with any
+    /// processes it started, and the build fails with the output it wrote so far. `None` waits
+    /// indefinitely. Defaults to 300 seconds.
+    pub timeout: Option<u64>,
+    /// Parse the Rust sources in the content directories and pass the class names used in
+    /// `view!` invocations to Tailwind explicitly, including the ones in `format!` arguments and
+    /// `if` branches that its text scanner can miss. Disabled by default.
+    pub extract_classes: bool,
+    /// The values used to generate `tailwind.config.js` if it doesn't exist yet.
+    pub config_template: ConfigTemplate,
+    /// A Tailwind config defined in Rust, used instead of `tailwind.config.js` (v3 only).\
+    /// Bundles with their own `config` still use that.
+    pub config: Option<TailwindConfig>,
+    /// The directory all paths are relative to, which is also the CLI's working directory.\
+    /// Defaults to the app's crate root, use [`manifest_dir!`] to capture it at compile time.
+    /// Otherwise the `CARGO_MANIFEST_DIR` set by `cargo run` (which the Perseus CLI uses) is
+    /// used, falling back to the working directory.
+    pub root: Option<PathBuf>,
+}
+
+/// Expands to the root directory of the crate this is used in, for [`TailwindOptions::root`].
+///
+/// ```
+/// let options = perseus_tailwind::TailwindOptions {
+///     root: Some(perseus_tailwind::manifest_dir!().into()),
+///     ..Default::default()
+/// };
+/// ```
+#[macro_export]
+macro_rules! manifest_dir {
+    () => {
+        env!("CARGO_MANIFEST_DIR")
+    };
+}
+
+impl Default for TailwindOptions {
+    fn default() -> Self {
+        Self {
+            in_file: "src/tailwind.css".into(),
+            out_file: "dist/static/tailwind.css".into(),
+            bundles: Vec::new(),
+            backend: TailwindBackendKind::default(),
+            executable: TailwindExecutable::default(),
+            install: None,
+            version: None,
+            major_version: None,
+            mode: TailwindMode::default(),
+            minify: None,
+            postcss: false,
+            incremental: true,
+            redirect_unsafe_out_file: false,
+            hashed_filenames: false,
+            serve_at: None,
+            inline_critical_css: false,
+            watch: false,
+            daemon: false,
+            background: false,
+            timeout: Some(300),
+            extract_classes: false,
+            config_template: ConfigTemplate::default(),
+            config: None,
+            root: None,
+        }
+    }
+}
+
+impl TailwindOptions {
+    /// Resolves the root directory the options' paths are relative to.
+    #[cfg(engine)]
+    fn resolve_root(&self) -> Result<PathBuf, TailwindError> {
+        let cwd = std::env::current_dir().map_err(|source| TailwindError::Io {
+            path: ".".into(),
+            source,
+        })?;
+        let root = match &self.root {
+            Some(root) => root.clone(),
+            None => std::env::var_os("CARGO_MANIFEST_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_default(),
+        };
+        Ok(cwd.join(root))
+    }
+
+    /// Applies the overrides and resolves the root, giving the options the plugin runs with.
+    #[cfg(engine)]
+    fn resolved(&self) -> Result<TailwindOptions, TailwindError> {
+        let mut options = self.with_overrides(&self.resolve_root()?)?;
+        // The overrides may have moved the root
+        options.root = Some(options.resolve_root()?);
+        options.validate()?;
+        Ok(options)
+    }
+
+    /// Rejects combinations of options that can't work together.
+    #[cfg(engine)]
+    fn validate(&self) -> Result<(), TailwindError> {
+        // Pages link the hashed files from when the server started, which the first rebuild
+        // would remove
+        if self.watch && self.hashed_filenames {
+            return Err(TailwindError::InvalidOptions {
+                origin: "watch".to_string(),
+                message: "the CSS can't be watched with `hashed_filenames`, e.g. use \
+                          `hashed_filenames: !cfg!(debug_assertions)`"
+                    .to_string(),
+            });
+        }
+        // The names are part of the file names of the bundles' fingerprints, classes and daemons
+        let bundles = self.bundles();
+        for (index, bundle) in bundles.iter().enumerate() {
+            let name = bundle.name.as_str();
+            let message = if matches!(name, "" | "." | "..") || name.contains(['/', '\\']) {
+                format!(
+                    "'{}' isn't a valid bundle name, it's used in file names",
+                    name
+                )
+            } else if bundles[..index].iter().any(|other| other.name == name) {
+                format!("there are several bundles named '{}'", name)
+            } else {
+                continue;
+            };
+            return Err(TailwindError::InvalidOptions {
+                origin: "bundles".to_string(),
+                message,
+            });
+        }
+        Ok(())
+    }
+
+    /// Resolves a path from the options against the root directory.
+    #[cfg(engine)]
+    pub(crate) fn path(&self, path: impl AsRef<Path>) -> PathBuf {
+        match &self.root {
+            Some(root) => root.join(path),
+            None => path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// The bundles to build, which is just `in_file`/`out_file` unless `bundles` is set.
+    #[cfg(engine)]
+    fn bundles(&self) -> Vec<TailwindBundle> {
+        if !self.bundles.is_empty() {
+            return self.bundles.clone();
+        }
+        let name = Path::new(&self.out_file)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "tailwind".to_string());
+        vec![TailwindBundle::new(name, &self.in_file, &self.out_file)]
+    }
 }
 
 /// The plugin constructor
@@ -66,8 +362,47 @@ pub fn get_tailwind_plugin() -> Plugin<TailwindOptions> {
                     .before_build
                     .register_plugin(PLUGIN_NAME, |_, data| {
                         if let Some(options) = data.downcast_ref::<TailwindOptions>() {
-                            try_run_tailwind(options);
-                            return Ok(())
+                            start_tailwind(options, EngineOperation::Build)?;
+                            Ok(())
+                        } else {
+                            unreachable!()
+                        }
+                    });
+                actions
+                    .build_actions
+                    .after_successful_build
+                    .register_plugin(PLUGIN_NAME, |_, _| Ok(background::join()?));
+                actions
+                    .build_actions
+                    .after_failed_build
+                    .register_plugin(PLUGIN_NAME, |_, _| Ok(background::join()?));
+                actions.settings_actions.add_static_aliases.register_plugin(
+                    PLUGIN_NAME,
+                    |_, data| {
+                        if let Some(options) = data.downcast_ref::<TailwindOptions>() {
+                            let options = options.resolved()?;
+                            // Perseus never runs `before_serve`, but the aliases are set up when
+                            // the server starts
+                            if options.watch
+                                && matches!(perseus::engine::get_op(), Some(EngineOperation::Serve))
+                            {
+                                watch::start(&options)?;
+                            }
+                            Ok(serve::static_aliases(&options)?)
+                        } else {
+                            unreachable!()
+                        }
+                    },
+                );
+                actions
+                    .settings_actions
+                    .html_shell_actions
+                    .add_to_head_before_boundary
+                    .register_plugin(PLUGIN_NAME, |_, data| {
+                        if let Some(options) = data.downcast_ref::<TailwindOptions>() {
+                            // The HTML shell is created before the build hooks run
+                            background::join()?;
+                            Ok(serve::head_links(&options.resolved()?)?)
                         } else {
                             unreachable!()
                         }
@@ -77,8 +412,30 @@ pub fn get_tailwind_plugin() -> Plugin<TailwindOptions> {
                     .before_export
                     .register_plugin(PLUGIN_NAME, |_, data| {
                         if let Some(options) = data.downcast_ref::<TailwindOptions>() {
-                            try_run_tailwind(options);
-                            return Ok(())
+                            start_tailwind(options, EngineOperation::Export)?;
+                            Ok(())
+                        } else {
+                            unreachable!()
+                        }
+                    });
+                actions
+                    .export_actions
+                    .after_successful_build
+                    .register_plugin(PLUGIN_NAME, |_, _| Ok(background::join()?));
+                actions
+                    .export_actions
+                    .after_failed_build
+                    .register_plugin(PLUGIN_NAME, |_, _| Ok(background::join()?));
+                actions
+                    .export_actions
+                    .after_successful_export
+                    .register_plugin(PLUGIN_NAME, |_, data| {
+                        if let Some(options) = data.downcast_ref::<TailwindOptions>() {
+                            let options = options.resolved()?;
+                            if options.inline_critical_css {
+                                critical::inline(&options)?;
+                            }
+                            Ok(())
                         } else {
                             unreachable!()
                         }
@@ -91,40 +448,229 @@ pub fn get_tailwind_plugin() -> Plugin<TailwindOptions> {
     )
 }
 
+/// Runs Tailwind, or starts running it in the background if `background` is set.
 #[cfg(engine)]
-fn try_run_tailwind(options: &TailwindOptions) {
-    if !PathBuf::from("tailwind.config.js").exists() {
-        init_tailwind();
+fn start_tailwind(
+    options: &TailwindOptions,
+    operation: EngineOperation,
+) -> Result<(), TailwindError> {
+    if options.resolved()?.background {
+        background::start(options.clone(), operation);
+        Ok(())
+    } else {
+        try_run_tailwind(options, operation)
     }
+}
 
-    let mut args = vec!["build", &options.in_file, "-o", &options.out_file];
-    if cfg!(not(debug_assertions)) {
-        args.push("-m");
-        args.push("-p");
+#[cfg(engine)]
+fn try_run_tailwind(
+    options: &TailwindOptions,
+    operation: EngineOperation,
+) -> Result<(), TailwindError> {
+    let options = &options.resolved()?;
+    let _lock = lock_build(options)?;
+    let root = options.resolve_root()?;
+    let mut backend = options.backend.create(options, &root)?;
+    let mut bundles = paths::output_bundles(options)?;
+    let mode = options.mode.resolve(operation);
+    let outputs = bundles.clone();
+    if options.incremental {
+        let mut stale = Vec::new();
+        for bundle in bundles {
+            let fingerprint = fingerprint::compute(options, &bundle, mode, backend.as_ref())?;
+            if fingerprint::is_fresh(&options.path(&bundle.out_file), &fingerprint) {
+                log::info!(
+                    "Tailwind output '{}' is up to date, skipping the build.",
+                    bundle.out_file
+                );
+            } else {
+                stale.push(bundle);
+            }
+        }
+        bundles = stale;
     }
+    if !bundles.is_empty() {
+        backend.prepare(options, &bundles)?;
+        build_bundles(options, backend.as_ref(), &bundles, mode)?;
+    }
+    // Up-to-date bundles are published as well, in case their hashed file was removed
+    if options.hashed_filenames {
+        manifest::publish(options, &outputs)?;
+    }
+    if options.serve_at.is_some() {
+        serve::publish(options, &outputs)?;
+    }
+    Ok(())
+}
 
-    let output = Command::new(format!("tailwindcli"))
-        .args(args)
-        .output()
-        .expect("Failed to run Tailwind CLI");
-    let output = String::from_utf8_lossy(&output.stderr);
-    // Errors always contain a JSON object. Please start using result codes Tailwind
-    // Also, don't write info messages to stderr instead of stdout
-    // Also if you're going to print JSON make the whole thing JSON and not some exception stack
-    // trace syntax followed by JSON
-    if output.contains('}') {
-        panic!("{}", output);
+/// Builds the bundles in parallel, storing their fingerprints as they finish.
+#[cfg(engine)]
+fn build_bundles(
+    options: &TailwindOptions,
+    backend: &dyn TailwindBackend,
+    bundles: &[TailwindBundle],
+    mode: TailwindMode,
+) -> Result<(), TailwindError> {
+    let build = |bundle: &TailwindBundle| {
+        backend.build(options, bundle, mode)?;
+        // Computed after the build since the input file may have just been generated.
+        if options.incremental {
+            let fingerprint = fingerprint::compute(options, bundle, mode, backend)?;
+            fingerprint::store(&options.path(&bundle.out_file), &fingerprint)?;
+        }
+        Ok(())
+    };
+    if let [bundle] = bundles {
+        return build(bundle);
     }
+    let results: Vec<Result<(), TailwindError>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = bundles
+            .iter()
+            .map(|bundle| {
+                let build = &build;
+                scope.spawn(move || build(bundle))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| match handle.join() {
+                Ok(result) => result,
+                Err(panic) => std::panic::resume_unwind(panic),
+            })
+            .collect()
+    });
+    // Report every failed bundle, but fail the build with the first error.
+    let mut errors = bundles
+        .iter()
+        .zip(results)
+        .filter_map(|(bundle, result)| result.err().map(|err| (bundle, err)));
+    match errors.next() {
+        Some((_, first)) => {
+            for (bundle, err) in errors {
+                log::error!("Tailwind bundle '{}' failed: {}", bundle.name, err);
+            }
+            Err(first)
+        }
+        None => Ok(()),
+    }
+}
+
+/// Formats bytes, e.g. a SHA-256 digest, as lowercase hex.
+#[cfg(engine)]
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Waits for other builds of the app to finish, keeping them waiting until the returned file is
+/// dropped.
+#[cfg(engine)]
+fn lock_build(options: &TailwindOptions) -> Result<File, TailwindError> {
+    let path = options.path(BUILD_LOCK);
+    let io_error = |source| TailwindError::Io {
+        path: path.clone(),
+        source,
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(io_error)?;
+    }
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .map_err(io_error)?;
+    file.lock().map_err(io_error)?;
+    Ok(file)
 }
 
+/// Writes a generated file, leaving it untouched if it is unchanged so its modification time
+/// only changes when its contents do.
 #[cfg(engine)]
-fn init_tailwind() {
+fn write_if_changed(path: &Path, contents: &str) -> Result<(), TailwindError> {
+    if std::fs::read_to_string(path).ok().as_deref() == Some(contents) {
+        return Ok(());
+    }
+    let io_error = |source| TailwindError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(io_error)?;
+    }
+    std::fs::write(path, contents).map_err(io_error)
+}
+
+#[cfg(engine)]
+fn init_tailwind(path: &Path, template: &ConfigTemplate) -> Result<(), TailwindError> {
     log::info!(
-        "Initializing Tailwind to search all Rust files in 'src' and all HTML files in 'static'."
+        "Initializing Tailwind to search {}.",
+        template.content.join(", ")
     );
-    let default_config = include_bytes!("default-config.js");
-    let mut config = File::create("tailwind.config.js").expect("Failed to create config file");
-    config
-        .write_all(default_config)
-        .expect("Failed to write default config");
+    File::create(path)
+        .and_then(|mut config| config.write_all(template.render().as_bytes()))
+        .map_err(|source| TailwindError::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+}
+
+#[cfg(all(test, engine))]
+mod tests {
+    use super::*;
+
+    fn options(root: &Path) -> TailwindOptions {
+        TailwindOptions {
+            root: Some(root.to_path_buf()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rejects_watching_hashed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = TailwindOptions {
+            watch: true,
+            hashed_filenames: true,
+            ..options(dir.path())
+        };
+        assert!(matches!(
+            options.resolved(),
+            Err(TailwindError::InvalidOptions { .. })
+        ));
+        assert!(TailwindOptions {
+            hashed_filenames: false,
+            ..options
+        }
+        .resolved()
+        .is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_and_duplicate_bundle_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = |name: &str| TailwindBundle::new(name, "in.css", format!("dist/{}.css", name));
+        let resolve = |names: &[&str]| {
+            TailwindOptions {
+                bundles: names.iter().map(|name| bundle(name)).collect(),
+                ..options(dir.path())
+            }
+            .resolved()
+        };
+
+        assert!(resolve(&["app", "admin"]).is_ok());
+        for names in [
+            &["app", "app"][..],
+            &[""],
+            &["."],
+            &[".."],
+            &["admin/app"],
+            &["admin\\app"],
+        ] {
+            assert!(
+                matches!(resolve(names), Err(TailwindError::InvalidOptions { .. })),
+                "{:?}",
+                names
+            );
+        }
+    }
 }
@@ -0,0 +1,230 @@
+#[cfg(engine)]
+use {
+    crate::{hex, TailwindBundle, TailwindError, TailwindOptions},
+    sha2::{Digest, Sha256},
+    std::{
+        collections::BTreeMap,
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+/// The manifest written next to the output files when
+/// [`TailwindOptions::hashed_filenames`](crate::TailwindOptions) is set. It is a JSON object
+/// mapping the configured file names to the hashed ones, e.g.
+/// `{"tailwind.css": "tailwind.1a2b3c4d.css"}`.
+pub static MANIFEST_FILE: &str = "tailwind-manifest.json";
+
+/// The number of hex digits of the content hash in file names.
+#[cfg(engine)]
+static HASH_LEN: usize = 8;
+
+/// Returns the URL of the current version of an output file, for the `<link>` tag in the head of
+/// your pages.
+///
+/// `url` is the URL the output file would be served at under its configured name and `out_file`
+/// the configured path, relative to the directory the server runs in. If the file was written
+/// with [`hashed_filenames`](crate::TailwindOptions), the file name at the end of the URL is
+/// replaced with the current hashed one, so serve the directory of the output rather than the
+/// file itself:
+///
+/// ```
+/// # use perseus::prelude::*;
+/// // `out_file` is `dist/static/tailwind.css`
+/// # let app = PerseusApp::<PerseusNodeType>::new()
+/// .static_alias("/styles", "dist/static")
+/// # ;
+///
+/// // In the head of a page, for `link(rel = "stylesheet", href = href)`
+/// let href = perseus_tailwind::stylesheet_url("/styles/tailwind.css", "dist/static/tailwind.css");
+/// ```
+///
+/// The URL is returned unchanged if there is no manifest entry for the file, and in the browser,
+/// where the head of pages isn't rendered, so it can be called from view code compiled for both.
+#[cfg(engine)]
+pub fn stylesheet_url(url: &str, out_file: &str) -> String {
+    let out_file = Path::new(out_file);
+    let hashed = out_file
+        .parent()
+        .map(|dir| read_manifest(&dir.join(MANIFEST_FILE)))
+        .zip(out_file.file_name())
+        .and_then(|(mut manifest, name)| manifest.remove(name.to_string_lossy().as_ref()));
+    match (hashed, url.rsplit_once('/')) {
+        (Some(hashed), Some((base, _))) => format!("{}/{}", base, hashed),
+        (Some(hashed), None) => hashed,
+        (None, _) => url.to_string(),
+    }
+}
+
+/// Returns `url` unchanged, since the head of pages is only rendered on the server. See the engine
+/// version for details.
+#[cfg(not(engine))]
+pub fn stylesheet_url(url: &str, _out_file: &str) -> String {
+    url.to_string()
+}
+
+/// Writes the hashed copies of the bundles' output files, removes outdated ones and records the
+/// current names in the manifests next to them.
+#[cfg(engine)]
+pub(crate) fn publish(
+    options: &TailwindOptions,
+    bundles: &[TailwindBundle],
+) -> Result<(), TailwindError> {
+    let mut manifests: BTreeMap<PathBuf, BTreeMap<String, String>> = BTreeMap::new();
+    for bundle in bundles {
+        let out_file = options.path(&bundle.out_file);
+        let io_error = |path: &Path| {
+            let path = path.to_path_buf();
+            move |source| TailwindError::Io { path, source }
+        };
+        let css = fs::read(&out_file).map_err(io_error(&out_file))?;
+        let hash = hex(&Sha256::digest(&css));
+        let (stem, extension) = stem_and_extension(&out_file);
+        let hashed = format!("{}.{}{}", stem, &hash[..HASH_LEN], extension);
+
+        let dir = out_file
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .to_path_buf();
+        let hashed_path = dir.join(&hashed);
+        if !hashed_path.exists() {
+            fs::write(&hashed_path, &css).map_err(io_error(&hashed_path))?;
+            log::info!(
+                "Wrote '{}' for bundle '{}'.",
+                hashed_path.display(),
+                bundle.name
+            );
+        }
+        for entry in fs::read_dir(&dir).map_err(io_error(&dir))?.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name != hashed && is_hashed_name(&name, &stem, &extension) {
+                log::debug!("Removing outdated '{}'.", entry.path().display());
+                fs::remove_file(entry.path()).map_err(io_error(&entry.path()))?;
+            }
+        }
+
+        let name = out_file
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        manifests.entry(dir).or_default().insert(name, hashed);
+    }
+
+    for (dir, entries) in manifests {
+        let path = dir.join(MANIFEST_FILE);
+        let mut manifest = read_manifest(&path);
+        manifest.extend(entries);
+        let json = serde_json::to_string_pretty(&manifest).unwrap_or_default();
+        if fs::read_to_string(&path).ok().as_deref() != Some(json.as_str()) {
+            fs::write(&path, json).map_err(|source| TailwindError::Io { path, source })?;
+        }
+    }
+    Ok(())
+}
+
+/// The hashed name recorded for an output file in the manifest next to it.
+#[cfg(engine)]
+pub(crate) fn hashed_name(out_file: &Path) -> Option<String> {
+    let name = out_file.file_name()?.to_string_lossy().into_owned();
+    read_manifest(&out_file.parent()?.join(MANIFEST_FILE)).remove(&name)
+}
+
+#[cfg(engine)]
+fn read_manifest(path: &Path) -> BTreeMap<String, String> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Splits `tailwind.css` into `tailwind` and `.css`.
+#[cfg(engine)]
+pub(crate) fn stem_and_extension(path: &Path) -> (String, String) {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    (stem, extension)
+}
+
+/// Checks whether a file name is a hashed version of the given output file.
+#[cfg(engine)]
+pub(crate) fn is_hashed_name(name: &str, stem: &str, extension: &str) -> bool {
+    name.strip_prefix(stem)
+        .and_then(|rest| rest.strip_prefix('.'))
+        .and_then(|rest| rest.strip_suffix(extension))
+        .map(|hash| hash.len() == HASH_LEN && hash.chars().all(|c| c.is_ascii_hexdigit()))
+        .unwrap_or(false)
+}
+
+#[cfg(all(test, engine))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_hashed_names() {
+        let cases = [
+            ("tailwind.1a2b3c4d.css", true),
+            ("tailwind.css", false),
+            ("tailwind.1a2b3c4.css", false),
+            ("tailwind.1a2b3c4g.css", false),
+            ("tailwind.1a2b3c4d.js", false),
+            ("tailwind-admin.1a2b3c4d.css", false),
+        ];
+        for (name, hashed) in cases {
+            assert_eq!(is_hashed_name(name, "tailwind", ".css"), hashed, "{}", name);
+        }
+    }
+
+    #[test]
+    fn replaces_the_file_name_of_stylesheet_urls() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join(MANIFEST_FILE),
+            r#"{"tailwind.css": "tailwind.1a2b3c4d.css"}"#,
+        )
+        .unwrap();
+        let out_file = dir.path().join("tailwind.css").display().to_string();
+        let admin = dir.path().join("admin.css").display().to_string();
+
+        assert_eq!(
+            stylesheet_url("/styles/tailwind.css", &out_file),
+            "/styles/tailwind.1a2b3c4d.css"
+        );
+        assert_eq!(
+            stylesheet_url("tailwind.css", &out_file),
+            "tailwind.1a2b3c4d.css"
+        );
+        assert_eq!(
+            stylesheet_url("/styles/admin.css", &admin),
+            "/styles/admin.css"
+        );
+    }
+
+    #[test]
+    fn publish_removes_outdated_hashed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let dist = dir.path().join("dist");
+        fs::create_dir(&dist).unwrap();
+        fs::write(dist.join("tailwind.css"), ".a{}").unwrap();
+        fs::write(dist.join("tailwind.00000000.css"), ".old{}").unwrap();
+        fs::write(dist.join("admin.00000000.css"), ".admin{}").unwrap();
+        let options = TailwindOptions {
+            root: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let bundle = TailwindBundle::new("tailwind", "src/tailwind.css", "dist/tailwind.css");
+
+        publish(&options, &[bundle]).unwrap();
+
+        let hashed = format!("tailwind.{}.css", &hex(&Sha256::digest(".a{}"))[..HASH_LEN]);
+        assert_eq!(fs::read_to_string(dist.join(&hashed)).unwrap(), ".a{}");
+        assert!(!dist.join("tailwind.00000000.css").exists());
+        assert!(dist.join("admin.00000000.css").exists());
+        assert_eq!(hashed_name(&dist.join("tailwind.css")), Some(hashed));
+    }
+}
@@ -0,0 +1,46 @@
+#[cfg(engine)]
+use perseus::engine::EngineOperation;
+use serde::{Deserialize, Serialize};
+
+/// Whether Tailwind should produce development or production CSS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TailwindMode {
+    /// Unminified output, for fast rebuilds and readable CSS.
+    Development,
+    /// Minified and optimized output.
+    Production,
+    /// Production for exports and for apps whose engine was built in release mode (e.g. by
+    /// `perseus deploy`), development otherwise.
+    #[default]
+    Auto,
+}
+
+impl TailwindMode {
+    /// Resolves [`TailwindMode::Auto`] from the Perseus operation that triggered the plugin.
+    #[cfg(engine)]
+    pub(crate) fn resolve(self, operation: EngineOperation) -> Self {
+        match self {
+            TailwindMode::Auto if matches!(operation, EngineOperation::Export) => {
+                TailwindMode::Production
+            }
+            TailwindMode::Auto if is_release_engine() => TailwindMode::Production,
+            TailwindMode::Auto => TailwindMode::Development,
+            mode => mode,
+        }
+    }
+}
+
+/// Checks whether the app's engine was built with the release profile. The Perseus CLI builds the
+/// engine into Cargo's usual target layout, so the profile shows up in the path of the executable.
+/// If that can't be determined, this falls back to the profile this crate was compiled with.
+#[cfg(engine)]
+fn is_release_engine() -> bool {
+    match std::env::current_exe() {
+        Ok(exe) => exe
+            .parent()
+            .map(|dir| dir.ends_with("release") || dir.ends_with("release/deps"))
+            .unwrap_or(cfg!(not(debug_assertions))),
+        Err(_) => cfg!(not(debug_assertions)),
+    }
+}
@@ -0,0 +1,202 @@
+#[cfg(engine)]
+use {
+    crate::{TailwindError, TailwindOptions},
+    std::{env, fs, path::Path},
+    toml::{Table, Value},
+};
+
+/// The standalone options file, looked up next to `Cargo.toml`.
+pub static OPTIONS_FILE: &str = "perseus-tailwind.toml";
+
+/// The prefix of the environment variables that override individual options.
+pub static ENV_PREFIX: &str = "PERSEUS_TAILWIND_";
+
+#[cfg(engine)]
+impl TailwindOptions {
+    /// Layers the options from `Cargo.toml` metadata, [`OPTIONS_FILE`] and the environment on top
+    /// of these options.
+    pub(crate) fn with_overrides(&self, root: &Path) -> Result<TailwindOptions, TailwindError> {
+        let mut options = to_table(self, "the plugin options")?;
+
+        let manifest_path = root.join("Cargo.toml");
+        if let Some(manifest) = read_table(&manifest_path)? {
+            let metadata = manifest
+                .get("package")
+                .and_then(|package| package.get("metadata"))
+                .and_then(|metadata| metadata.get("perseus-tailwind"));
+            match metadata {
+                Some(Value::Table(metadata)) => merge(&mut options, metadata.clone()),
+                Some(_) => {
+                    return Err(TailwindError::InvalidOptions {
+                        origin: manifest_path.display().to_string(),
+                        message: "`package.metadata.perseus-tailwind` must be a table".to_string(),
+                    })
+                }
+                None => {}
+            }
+        }
+        if let Some(file) = read_table(&root.join(OPTIONS_FILE))? {
+            merge(&mut options, file);
+        }
+        let options = from_table(options, "the config files")?;
+        apply_env(options)
+    }
+}
+
+/// Overrides top-level fields from `PERSEUS_TAILWIND_<FIELD>` environment variables.
+///
+/// Values are parsed as TOML values so booleans, arrays and inline tables can be set. If the
+/// result doesn't fit the field, e.g. `PERSEUS_TAILWIND_VERSION=3.4` parsing as a float, the
+/// raw value is used as a string instead.
+#[cfg(engine)]
+fn apply_env(options: TailwindOptions) -> Result<TailwindOptions, TailwindError> {
+    let mut options = to_table(&options, "the plugin options")?;
+    let mut vars: Vec<(String, String)> = env::vars()
+        .filter_map(|(name, value)| {
+            let field = name.strip_prefix(ENV_PREFIX)?.to_ascii_lowercase();
+            Some((field, value))
+        })
+        .filter(|(field, _)| field != "bin")
+        .collect();
+    vars.sort();
+
+    for (field, raw) in vars {
+        let origin = format!("{}{}", ENV_PREFIX, field.to_ascii_uppercase());
+        let parsed = toml::from_str::<Table>(&format!("value = {}", raw))
+            .ok()
+            .and_then(|mut table| table.remove("value"));
+        let mut candidates = parsed.into_iter().chain([Value::String(raw)]).peekable();
+        while let Some(value) = candidates.next() {
+            let mut candidate = options.clone();
+            merge(&mut candidate, Table::from_iter([(field.clone(), value)]));
+            match from_table(candidate.clone(), &origin) {
+                Ok(_) => {
+                    options = candidate;
+                    break;
+                }
+                Err(err) if candidates.peek().is_none() => return Err(err),
+                Err(_) => {}
+            }
+        }
+    }
+    from_table(options, "the environment")
+}
+
+/// Merges `overrides` into `base`, recursing into tables that exist on both sides.
+#[cfg(engine)]
+fn merge(base: &mut Table, overrides: Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base)), Value::Table(overrides)) => merge(base, overrides),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(engine)]
+fn read_table(path: &Path) -> Result<Option<Table>, TailwindError> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(path).map_err(|source| TailwindError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    toml::from_str(&contents)
+        .map(Some)
+        .map_err(|err| TailwindError::InvalidOptions {
+            origin: path.display().to_string(),
+            message: err.to_string(),
+        })
+}
+
+#[cfg(engine)]
+fn to_table(options: &TailwindOptions, origin: &str) -> Result<Table, TailwindError> {
+    Table::try_from(options).map_err(|err| TailwindError::InvalidOptions {
+        origin: origin.to_string(),
+        message: err.to_string(),
+    })
+}
+
+#[cfg(engine)]
+fn from_table(table: Table, origin: &str) -> Result<TailwindOptions, TailwindError> {
+    Value::Table(table)
+        .try_into()
+        .map_err(|err: toml::de::Error| TailwindError::InvalidOptions {
+            origin: origin.to_string(),
+            message: err.to_string(),
+        })
+}
+
+#[cfg(all(test, engine))]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// The environment is shared by all tests, so the ones that read it run one at a time.
+    static ENV: Mutex<()> = Mutex::new(());
+
+    fn project(manifest: &str, options_file: Option<&str>) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            format!("[package]\nname = \"app\"\n\n{}", manifest),
+        )
+        .unwrap();
+        if let Some(options_file) = options_file {
+            fs::write(dir.path().join(OPTIONS_FILE), options_file).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn env_overrides_options_file_overrides_metadata() {
+        let _env = ENV.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let dir = project(
+            "[package.metadata.perseus-tailwind]\n\
+             in_file = \"metadata.css\"\n\
+             out_file = \"dist/metadata.css\"\n\
+             timeout = 1\n",
+            Some("out_file = \"dist/file.css\"\ntimeout = 2\n"),
+        );
+        env::set_var("PERSEUS_TAILWIND_TIMEOUT", "3");
+        let options = TailwindOptions::default().with_overrides(dir.path());
+        env::remove_var("PERSEUS_TAILWIND_TIMEOUT");
+
+        let options = options.unwrap();
+        assert_eq!(options.in_file, "metadata.css");
+        assert_eq!(options.out_file, "dist/file.css");
+        assert_eq!(options.timeout, Some(3));
+    }
+
+    #[test]
+    fn merges_nested_tables() {
+        let _env = ENV.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let dir = project(
+            "[package.metadata.perseus-tailwind.config_template]\n\
+             theme_extend = \"{ colors: { brand: '#0f766e' } }\"\n",
+            Some("[config_template]\nplugins = [\"@tailwindcss/forms\"]\n"),
+        );
+
+        let options = TailwindOptions::default()
+            .with_overrides(dir.path())
+            .unwrap();
+        let template = options.config_template;
+        assert_eq!(template.theme_extend, "{ colors: { brand: '#0f766e' } }");
+        assert_eq!(template.plugins, ["@tailwindcss/forms"]);
+        assert_eq!(template.content, crate::DEFAULT_CONTENT);
+    }
+
+    #[test]
+    fn keeps_env_values_that_only_fit_as_strings() {
+        let _env = ENV.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let dir = project("", None);
+        env::set_var("PERSEUS_TAILWIND_VERSION", "3.4");
+        let options = TailwindOptions::default().with_overrides(dir.path());
+        env::remove_var("PERSEUS_TAILWIND_VERSION");
+
+        assert_eq!(options.unwrap().version.as_deref(), Some("3.4"));
+    }
+}
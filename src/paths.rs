@@ -0,0 +1,192 @@
+#![cfg(engine)]
+
+use crate::{TailwindBundle, TailwindError, TailwindOptions};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// Directories in the project that the Perseus CLI doesn't watch for changes, so output written
+/// there can't trigger a rebuild.
+static UNWATCHED_DIRS: &[&str] = &["dist", "target"];
+
+/// The bundles with the output files they are actually written to, see [`check_out_file`]. These
+/// are resolved once per process and set of options, so that the build, the served links,
+/// critical CSS and the watcher all agree on them and redirects are only reported once.
+pub(crate) fn output_bundles(
+    options: &TailwindOptions,
+) -> Result<Vec<TailwindBundle>, TailwindError> {
+    type Key = (PathBuf, Vec<TailwindBundle>, bool);
+    static RESOLVED: Mutex<Vec<(Key, Vec<TailwindBundle>)>> = Mutex::new(Vec::new());
+    let root = options.resolve_root()?;
+    let key = (root, options.bundles(), options.redirect_unsafe_out_file);
+    let mut resolved = RESOLVED
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some((_, bundles)) = resolved.iter().find(|(resolved, _)| *resolved == key) {
+        return Ok(bundles.clone());
+    }
+    let mut bundles = key.1.clone();
+    for bundle in &mut bundles {
+        bundle.out_file = check_out_file(&key.0, &bundle.out_file, key.2)?;
+    }
+    resolved.push((key, bundles.clone()));
+    Ok(bundles)
+}
+
+/// Makes sure an output file can't trigger build loops.
+///
+/// Anything inside the project that isn't in `dist` or `target` is watched by `perseus serve -w`
+/// (and `static` is copied into the app), so writing CSS there rebuilds the app, which runs
+/// Tailwind again. Such paths are rejected, or moved into `dist` if `redirect` is set. Paths are
+/// compared after resolving symlinks, so a symlink from `dist` into `static` is caught as well.
+pub(crate) fn check_out_file(
+    root: &Path,
+    out_file: &str,
+    redirect: bool,
+) -> Result<String, TailwindError> {
+    let io_error = |path: &Path| {
+        let path = path.to_path_buf();
+        move |source| TailwindError::Io { path, source }
+    };
+    let root = root.canonicalize().map_err(io_error(root))?;
+    let relative = match watched_path(&root, Path::new(out_file))? {
+        Some(relative) => relative,
+        None => return Ok(out_file.to_string()),
+    };
+
+    let redirected = Path::new("dist").join(&relative);
+    let redirected = redirected.to_string_lossy().replace('\\', "/");
+    let url = format!("/{}", relative.to_string_lossy().replace('\\', "/"));
+    // Redirecting doesn't help if `dist` itself leads somewhere that is watched
+    if redirect && watched_path(&root, Path::new(&redirected))?.is_none() {
+        log::warn!(
+            "Tailwind output '{}' is watched by Perseus and would cause build loops, writing it \
+             to '{}' instead. Serve it with `.static_alias(\"{}\", \"{}\")`.",
+            out_file,
+            redirected,
+            url,
+            redirected
+        );
+        return Ok(redirected);
+    }
+    Err(TailwindError::UnsafeOutFile {
+        out_file: out_file.to_string(),
+        suggestion: format!(
+            "write it to '{}' and add `.static_alias(\"{}\", \"{}\")` to your app",
+            redirected, url, redirected
+        ),
+    })
+}
+
+/// Returns the path relative to the project root if it is watched by Perseus, i.e. if it is
+/// inside `static`, or inside the project but not in one of the unwatched directories.
+fn watched_path(root: &Path, path: &Path) -> Result<Option<PathBuf>, TailwindError> {
+    let resolve = |path: &Path| {
+        resolve(&root.join(path)).map_err(|source| TailwindError::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+    };
+    let resolved = resolve(path)?;
+    let relative = match resolved.strip_prefix(root) {
+        // Files outside the project can't trigger a rebuild
+        Err(_) => return Ok(None),
+        Ok(relative) => relative.to_path_buf(),
+    };
+    if resolved.starts_with(resolve(Path::new("static"))?) {
+        return Ok(Some(relative));
+    }
+    for dir in UNWATCHED_DIRS {
+        if resolved.starts_with(resolve(Path::new(dir))?) {
+            return Ok(None);
+        }
+    }
+    Ok(Some(relative))
+}
+
+/// Canonicalizes a path that may not exist yet by canonicalizing its closest existing ancestor.
+fn resolve(path: &Path) -> io::Result<PathBuf> {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    while !existing.exists() {
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name);
+                existing = parent;
+            }
+            _ => break,
+        }
+    }
+    let mut resolved = existing.canonicalize()?;
+    resolved.extend(rest.into_iter().rev());
+    Ok(resolved)
+}
+
+#[cfg(all(test, engine))]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("static")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn rejects_outputs_in_static() {
+        let dir = project();
+        let result = check_out_file(dir.path(), "static/x.css", false);
+        assert!(matches!(result, Err(TailwindError::UnsafeOutFile { .. })));
+    }
+
+    #[test]
+    fn redirects_outputs_in_static_into_dist() {
+        let dir = project();
+        let out_file = check_out_file(dir.path(), "static/x.css", true).unwrap();
+        assert_eq!(out_file, "dist/static/x.css");
+    }
+
+    #[test]
+    fn accepts_outputs_in_dist() {
+        let dir = project();
+        let out_file = check_out_file(dir.path(), "dist/x.css", false).unwrap();
+        assert_eq!(out_file, "dist/x.css");
+        let root = dir.path().canonicalize().unwrap();
+        assert_eq!(watched_path(&root, Path::new("dist/x.css")).unwrap(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_a_dist_symlink_into_static() {
+        let dir = project();
+        std::os::unix::fs::symlink(dir.path().join("static"), dir.path().join("dist")).unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        assert_eq!(
+            watched_path(&root, Path::new("dist/x.css")).unwrap(),
+            Some(PathBuf::from("static/x.css"))
+        );
+        for redirect in [false, true] {
+            let result = check_out_file(dir.path(), "dist/x.css", redirect);
+            assert!(matches!(result, Err(TailwindError::UnsafeOutFile { .. })));
+        }
+    }
+
+    #[test]
+    fn resolves_output_bundles_per_set_of_options() {
+        let dir = project();
+        let options = |out_file: &str| TailwindOptions {
+            root: Some(dir.path().to_path_buf()),
+            out_file: out_file.to_string(),
+            redirect_unsafe_out_file: true,
+            ..Default::default()
+        };
+
+        let bundles = output_bundles(&options("static/app.css")).unwrap();
+        assert_eq!(bundles[0].out_file, "dist/static/app.css");
+        let bundles = output_bundles(&options("dist/other.css")).unwrap();
+        assert_eq!(bundles[0].out_file, "dist/other.css");
+    }
+}
@@ -0,0 +1,116 @@
+#![cfg(all(engine, feature = "railwind"))]
+
+use crate::{
+    backend::TailwindBackend, extract, TailwindBundle, TailwindError, TailwindMode, TailwindOptions,
+};
+use railwind::{CollectionOptions, Source};
+use std::fs;
+
+/// At-rules of the input CSS that only the Tailwind CLI understands.
+static UNSUPPORTED_AT_RULES: &[&str] = &["@apply", "@layer", "@theme", "@plugin", "@utility"];
+
+/// Generates CSS in-process with railwind.
+pub(crate) struct RailwindBackend;
+
+impl TailwindBackend for RailwindBackend {
+    fn identity(&self) -> String {
+        "railwind".to_string()
+    }
+
+    fn prepare(
+        &mut self,
+        options: &TailwindOptions,
+        bundles: &[TailwindBundle],
+    ) -> Result<(), TailwindError> {
+        if options.config.is_some() || bundles.iter().any(|bundle| bundle.config.is_some()) {
+            log::warn!(
+                "railwind only supports Tailwind's default theme, ignoring the Tailwind config."
+            );
+        }
+        if options.postcss {
+            log::warn!("railwind doesn't support PostCSS processing, ignoring `postcss`.");
+        }
+        Ok(())
+    }
+
+    fn build(
+        &self,
+        options: &TailwindOptions,
+        bundle: &TailwindBundle,
+        mode: TailwindMode,
+    ) -> Result<(), TailwindError> {
+        let in_file = options.path(&bundle.in_file);
+        let input = if in_file.exists() {
+            fs::read_to_string(&in_file).map_err(|source| TailwindError::Io {
+                path: in_file.clone(),
+                source,
+            })?
+        } else {
+            "@tailwind base;\n@tailwind components;\n@tailwind utilities;\n".to_string()
+        };
+        let classes = extract::bundle_classes(bundle, options)?;
+        log::debug!(
+            "Generating CSS for {} class names in bundle '{}' with railwind.",
+            classes.len(),
+            bundle.name
+        );
+
+        let mut warnings = Vec::new();
+        let mut generate = |classes: String, preflight: bool| {
+            railwind::parse_to_string(
+                Source::String(classes, CollectionOptions::String),
+                preflight,
+                &mut warnings,
+            )
+        };
+        let preflight = generate(String::new(), true);
+        let utilities = generate(classes.into_iter().collect::<Vec<_>>().join("\n"), false);
+        for warning in warnings {
+            log::debug!("[{}] {}", bundle.name, warning);
+        }
+
+        // Replace the Tailwind directives with the generated CSS and copy everything else
+        let mut css = String::new();
+        for line in input.lines() {
+            let directive = line.trim().trim_end_matches(';').trim();
+            match directive {
+                "@tailwind base" => css.push_str(&preflight),
+                "@tailwind utilities" => css.push_str(&utilities),
+                "@import \"tailwindcss\"" | "@import 'tailwindcss'" => {
+                    css.push_str(&preflight);
+                    css.push_str(&utilities);
+                }
+                _ if directive.starts_with("@tailwind")
+                    || directive.starts_with("@source")
+                    || directive.starts_with("@config") => {}
+                _ => {
+                    if let Some(rule) = UNSUPPORTED_AT_RULES
+                        .iter()
+                        .find(|rule| directive.starts_with(*rule))
+                    {
+                        log::warn!(
+                            "[{}] railwind doesn't support `{}`, copying it to the output as is.",
+                            bundle.name,
+                            rule
+                        );
+                    }
+                    css.push_str(line);
+                    css.push('\n');
+                }
+            }
+        }
+        if options.minify.unwrap_or(mode == TailwindMode::Production) {
+            log::debug!("railwind doesn't minify, writing unminified CSS.");
+        }
+
+        let out_file = options.path(&bundle.out_file);
+        let io_error = |source| TailwindError::Io {
+            path: out_file.clone(),
+            source,
+        };
+        if let Some(parent) = out_file.parent() {
+            fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        fs::write(&out_file, css).map_err(io_error)
+    }
+}
@@ -0,0 +1,172 @@
+#![cfg(engine)]
+
+use crate::{manifest, paths, TailwindBundle, TailwindError, TailwindOptions};
+use std::{collections::HashMap, fs, path::Path};
+
+/// The directory the current output files are copied to when `serve_at` is set. Serving this
+/// directory rather than the outputs themselves keeps the static alias valid when hashed file
+/// names change, since Perseus reads the aliases before the plugin builds the CSS.
+static PUBLIC_DIR: &str = "dist/perseus-tailwind/public";
+
+/// The static alias that serves the output files at `serve_at`.
+pub(crate) fn static_aliases(
+    options: &TailwindOptions,
+) -> Result<HashMap<String, String>, TailwindError> {
+    let Some(url) = serve_url(options)? else {
+        return Ok(HashMap::new());
+    };
+    // Perseus only accepts aliases relative to the directory the app runs in
+    let public_dir = options.path(PUBLIC_DIR);
+    let cwd = std::env::current_dir().map_err(|source| TailwindError::Io {
+        path: ".".into(),
+        source,
+    })?;
+    let relative = public_dir
+        .strip_prefix(&cwd)
+        .map_err(|_| TailwindError::InvalidOptions {
+            origin: "serve_at".to_string(),
+            message: format!(
+                "'{}' has to be inside the directory the app runs in to be served",
+                public_dir.display()
+            ),
+        })?;
+    fs::create_dir_all(&public_dir).map_err(|source| TailwindError::Io {
+        path: public_dir.clone(),
+        source,
+    })?;
+    Ok(HashMap::from([(
+        url,
+        relative.to_string_lossy().replace('\\', "/"),
+    )]))
+}
+
+/// The stylesheet `<link>`s for the bundles' current output files.
+pub(crate) fn head_links(options: &TailwindOptions) -> Result<Vec<String>, TailwindError> {
+    let Some(url) = serve_url(options)? else {
+        return Ok(Vec::new());
+    };
+    let served = served_files(options);
+    let prefix = perseus::utils::get_path_prefix_server();
+    let links = paths::output_bundles(options)?
+        .iter()
+        .filter_map(|bundle| {
+            let name = served_name(bundle, options, &served);
+            if name.is_none() {
+                log::warn!(
+                    "No output of bundle '{}' to link yet, build the app first.",
+                    bundle.name
+                );
+            }
+            name
+        })
+        .map(|name| {
+            format!(
+                r#"<link rel="stylesheet" href="{}{}/{}">"#,
+                prefix, url, name
+            )
+        })
+        .collect();
+    Ok(links)
+}
+
+/// Copies the bundles' current output files into the served directory and removes everything
+/// else from it.
+pub(crate) fn publish(
+    options: &TailwindOptions,
+    bundles: &[TailwindBundle],
+) -> Result<(), TailwindError> {
+    let public_dir = options.path(PUBLIC_DIR);
+    let io_error = |path: &Path| {
+        let path = path.to_path_buf();
+        move |source| TailwindError::Io { path, source }
+    };
+    fs::create_dir_all(&public_dir).map_err(io_error(&public_dir))?;
+
+    let mut published: HashMap<String, &TailwindBundle> = HashMap::new();
+    for bundle in bundles {
+        let out_file = options.path(&bundle.out_file);
+        let name = file_name(&out_file);
+        let name = if options.hashed_filenames {
+            manifest::hashed_name(&out_file).unwrap_or(name)
+        } else {
+            name
+        };
+        if let Some(other) = published.insert(name.clone(), bundle) {
+            return Err(TailwindError::InvalidOptions {
+                origin: "serve_at".to_string(),
+                message: format!(
+                    "bundles '{}' and '{}' both write '{}', which can't be served from the same URL",
+                    other.name, bundle.name, name
+                ),
+            });
+        }
+        let source = out_file.with_file_name(&name);
+        let target = public_dir.join(&name);
+        if fs::read(&source).ok() != fs::read(&target).ok() {
+            fs::copy(&source, &target).map_err(io_error(&source))?;
+        }
+    }
+
+    for entry in fs::read_dir(&public_dir)
+        .map_err(io_error(&public_dir))?
+        .flatten()
+    {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !published.contains_key(&name) {
+            fs::remove_file(entry.path()).map_err(io_error(&entry.path()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Normalizes `serve_at` to an absolute URL path without a trailing slash.
+fn serve_url(options: &TailwindOptions) -> Result<Option<String>, TailwindError> {
+    let Some(url) = &options.serve_at else {
+        return Ok(None);
+    };
+    let url = url.trim_matches('/');
+    if url.is_empty() {
+        return Err(TailwindError::InvalidOptions {
+            origin: "serve_at".to_string(),
+            message: "the output files can't be served at the root of the app".to_string(),
+        });
+    }
+    Ok(Some(format!("/{}", url)))
+}
+
+fn served_files(options: &TailwindOptions) -> Vec<String> {
+    fs::read_dir(options.path(PUBLIC_DIR))
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Finds the file a bundle's output was published as. Only the file name of an output is kept, so
+/// this is independent of where it was written to.
+fn served_name(
+    bundle: &TailwindBundle,
+    options: &TailwindOptions,
+    served: &[String],
+) -> Option<String> {
+    let out_file = Path::new(&bundle.out_file);
+    let name = file_name(out_file);
+    if options.hashed_filenames {
+        let (stem, extension) = manifest::stem_and_extension(out_file);
+        served
+            .iter()
+            .find(|served| manifest::is_hashed_name(served, &stem, &extension))
+            .cloned()
+    } else {
+        served.contains(&name).then_some(name)
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+#[cfg(engine)]
+use {
+    crate::{executable::ResolvedExecutable, TailwindError},
+    semver::{Version, VersionReq},
+};
+
+/// The major version of Tailwind, which decides how it is configured and invoked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TailwindMajorVersion {
+    /// Tailwind v3, configured through `tailwind.config.js`
+    V3,
+    /// Tailwind v4, configured in CSS and distributed as the `@tailwindcss/cli` package
+    V4,
+}
+
+impl TailwindMajorVersion {
+    #[cfg(engine)]
+    pub(crate) fn of(version: &Version) -> Self {
+        if version.major >= 4 {
+            Self::V4
+        } else {
+            Self::V3
+        }
+    }
+}
+
+/// Determines the version of the Tailwind CLI from the header of its `--help` output, which looks
+/// like `tailwindcss v3.4.17` (or `≈ tailwindcss v4.0.0` for v4).
+#[cfg(engine)]
+pub(crate) fn detect_version(executable: &ResolvedExecutable) -> Result<Version, TailwindError> {
+    let output = executable.output(&["--help"])?;
+    let text = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    text.split_whitespace()
+        .filter_map(|word| word.strip_prefix('v'))
+        .find_map(|version| Version::parse(version).ok())
+        .ok_or_else(|| TailwindError::UnknownVersion {
+            output: text.trim().to_string(),
+        })
+}
+
+/// Fails if the version of the Tailwind CLI doesn't satisfy the given semver requirement.
+#[cfg(engine)]
+pub(crate) fn check_version(
+    executable: &ResolvedExecutable,
+    requirement: &str,
+) -> Result<Version, TailwindError> {
+    let required =
+        VersionReq::parse(requirement).map_err(|err| TailwindError::InvalidVersionRequirement {
+            requirement: requirement.to_string(),
+            message: err.to_string(),
+        })?;
+    let found = detect_version(executable)?;
+    if !required.matches(&found) {
+        return Err(TailwindError::UnsupportedVersion {
+            found: found.to_string(),
+            required: required.to_string(),
+        });
+    }
+    log::debug!("Tailwind CLI version {} satisfies {}.", found, required);
+    Ok(found)
+}
@@ -0,0 +1,77 @@
+#![cfg(engine)]
+
+use crate::{fingerprint, paths, try_run_tailwind, TailwindError, TailwindOptions};
+use perseus::engine::EngineOperation;
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+/// How often the inputs are checked for changes.
+static POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Starts rebuilding the CSS whenever its inputs change, for as long as the server runs. This
+/// only happens once per process, however often the plugin's actions are run.
+pub(crate) fn start(options: &TailwindOptions) -> Result<(), TailwindError> {
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+    let options = options.clone();
+    let mut last = modification_times(&options)?;
+    log::info!("Watching the Tailwind inputs for changes.");
+    thread::Builder::new()
+        .name("perseus-tailwind-watch".to_string())
+        .spawn(move || loop {
+            thread::sleep(POLL_INTERVAL);
+            let current = match modification_times(&options) {
+                Ok(current) => current,
+                Err(err) => {
+                    log::error!("Failed to check the Tailwind inputs for changes: {}", err);
+                    continue;
+                }
+            };
+            if current == last {
+                continue;
+            }
+            log::info!("Tailwind inputs changed, rebuilding the CSS.");
+            match try_run_tailwind(&options, EngineOperation::Serve) {
+                Ok(()) => reload(),
+                Err(err) => log::error!("Failed to rebuild the CSS: {}", err),
+            }
+            // Compared to the state after the build, since it may have generated files
+            last = modification_times(&options).unwrap_or(current);
+        })
+        .map_err(|source| TailwindError::Io {
+            path: ".".into(),
+            source,
+        })?;
+    Ok(())
+}
+
+/// The modification times of the inputs of all bundles.
+fn modification_times(
+    options: &TailwindOptions,
+) -> Result<Vec<(PathBuf, Option<SystemTime>)>, TailwindError> {
+    let mut times = Vec::new();
+    for bundle in paths::output_bundles(options)? {
+        times.extend(fingerprint::modification_times(options, &bundle)?);
+    }
+    Ok(times)
+}
+
+/// Tells the Perseus CLI's live reload server to reload the pages open in the browser, if it runs.
+fn reload() {
+    if std::env::var_os("PERSEUS_USE_RELOAD_SERVER").is_none() {
+        return;
+    }
+    let host =
+        std::env::var("PERSEUS_RELOAD_SERVER_HOST").unwrap_or_else(|_| "localhost".to_string());
+    let port = std::env::var("PERSEUS_RELOAD_SERVER_PORT").unwrap_or_else(|_| "3100".to_string());
+    if let Err(err) = reqwest::blocking::get(format!("http://{}:{}/send", host, port)) {
+        log::warn!("Failed to notify the live reload server: {}", err);
+    }
+}